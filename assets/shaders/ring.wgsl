@@ -0,0 +1,24 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+struct RingMaterial {
+    color: vec4<f32>,
+    // Relative to the radius
+    line_width: f32,
+};
+
+@group(2) @binding(0) var<uniform> material: RingMaterial;
+
+// Same as RING_MESH_HALF_SIZE in ring.rs
+const MESH_HALF_SIZE: f32 = 1.1;
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    // 1.0 on the circle
+    let distance = length(mesh.uv * 2.0 - 1.0) * MESH_HALF_SIZE;
+    let half_width = material.line_width / 2.0;
+    // Soft edges instead of jagged ones
+    let smoothing = fwidth(distance) / 2.0;
+    let coverage = 1.0 - smoothstep(half_width - smoothing, half_width + smoothing, abs(distance - 1.0));
+
+    return vec4<f32>(material.color.rgb, material.color.a * coverage);
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use vetovoima::{
+    app::GameMode, game::CountdownFrozen, headless::HeadlessGame, simulation::apply_forces,
+    tuning::Tuning,
+};
+
+// The first two are the amounts of the first and the later levels
+const DEBRIS_AMOUNTS: [u32; 4] = [16, 60, 200, 1000];
+
+// The first time trial level with exactly this many objects, frozen in time so that the
+// benchmark can't run out of it
+fn game_with_debris(debris_amount: u32) -> HeadlessGame {
+    let mut game = HeadlessGame::new(GameMode::TimeTrial);
+
+    game.app_mut().insert_resource(Tuning {
+        base_objects_amount: debris_amount,
+        max_objects_amount: debris_amount,
+        ..default()
+    });
+    game.app_mut().insert_resource(CountdownFrozen);
+    game.start_level();
+    game
+}
+
+fn physics_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("physics");
+
+    group.sample_size(20);
+
+    for debris_amount in DEBRIS_AMOUNTS {
+        group.bench_with_input(
+            BenchmarkId::new("apply_forces", debris_amount),
+            &debris_amount,
+            |b, debris_amount| {
+                let mut game = game_with_debris(*debris_amount);
+                let world = game.app_mut().world_mut();
+                let apply_forces = world.register_system(apply_forces);
+
+                b.iter(|| world.run_system(apply_forces).unwrap());
+            },
+        );
+
+        // Rapier runs in PostUpdate (along with the transform propagation)
+        group.bench_with_input(
+            BenchmarkId::new("rapier_step", debris_amount),
+            &debris_amount,
+            |b, debris_amount| {
+                let mut game = game_with_debris(*debris_amount);
+                let world = game.app_mut().world_mut();
+
+                b.iter(|| world.run_schedule(PostUpdate));
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, physics_benchmark);
+criterion_main!(benches);
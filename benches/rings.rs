@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use bevy_rapier2d::prelude::*;
+use criterion::{criterion_group, criterion_main, Criterion};
+use vetovoima::ring::{ring_scale, Ring, RingPlugin};
+
+// The gravity rings and the flag aura
+const RINGS_AMOUNT: usize = 7;
+const MAX_RADIUS: f32 = 500.0;
+
+// The rings used to be lyon shapes with a new path (and a new collider) every frame
+#[derive(Component)]
+struct RebuiltRing(f32);
+
+fn ring_app() -> App {
+    let mut app = App::new();
+
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        HierarchyPlugin,
+        AssetPlugin::default(),
+    ))
+    .init_asset::<Mesh>()
+    .init_asset::<ColorMaterial>()
+    .add_plugins((
+        ShapePlugin,
+        RingPlugin,
+        RapierPhysicsPlugin::<NoUserData>::default(),
+    ));
+
+    app
+}
+
+fn next_radius(radius: f32) -> f32 {
+    if radius > MAX_RADIUS {
+        1.0
+    } else {
+        radius + 1.0
+    }
+}
+
+fn ring_color(radius: f32) -> Color {
+    Color::hsla(220.0, 1.0, 0.5, 1.0 - radius / MAX_RADIUS)
+}
+
+// Every ring has a collider here, the aura is the only one in the game
+fn rebuilt_rings_setup(mut commands: Commands) {
+    for n in 0..RINGS_AMOUNT {
+        let radius = MAX_RADIUS / RINGS_AMOUNT as f32 * n as f32 + 1.0;
+        let shape = shapes::Circle {
+            radius,
+            center: Vec2::ZERO,
+        };
+
+        commands.spawn((
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shape),
+                ..Default::default()
+            },
+            Stroke::new(ring_color(radius), 1.0),
+            RebuiltRing(radius),
+            RigidBody::Fixed,
+            Collider::ball(radius),
+        ));
+    }
+}
+
+fn rebuild_rings(mut ring_query: Query<(&mut Path, &mut Stroke, &mut Collider, &mut RebuiltRing)>) {
+    for (mut path, mut stroke, mut collider, mut ring) in ring_query.iter_mut() {
+        let radius = next_radius(ring.0);
+        let shape = shapes::Circle {
+            radius,
+            center: Vec2::ZERO,
+        };
+
+        ring.0 = radius;
+        stroke.color = ring_color(radius);
+        *path = ShapePath::build_as(&shape);
+        *collider = Collider::ball(radius);
+    }
+}
+
+fn scaled_rings_setup(mut commands: Commands) {
+    for n in 0..RINGS_AMOUNT {
+        let radius = MAX_RADIUS / RINGS_AMOUNT as f32 * n as f32 + 1.0;
+
+        commands.spawn((
+            Ring {
+                color: ring_color(radius),
+            },
+            Transform::from_scale(ring_scale(radius)),
+            RigidBody::Fixed,
+            Collider::ball(1.0),
+        ));
+    }
+}
+
+fn scale_rings(mut ring_query: Query<(&mut Transform, &mut Ring)>) {
+    for (mut transform, mut ring) in ring_query.iter_mut() {
+        let radius = next_radius(transform.scale.x);
+
+        transform.scale = ring_scale(radius);
+        ring.color = ring_color(radius);
+    }
+}
+
+fn ring_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rings");
+
+    group.bench_function("rebuild_paths_and_colliders", |b| {
+        let mut app = ring_app();
+
+        app.add_systems(Startup, rebuilt_rings_setup)
+            .add_systems(Update, rebuild_rings);
+        app.update();
+
+        b.iter(|| app.update());
+    });
+
+    group.bench_function("scale_transforms", |b| {
+        let mut app = ring_app();
+
+        app.add_systems(Startup, scaled_rings_setup)
+            .add_systems(Update, scale_rings);
+        app.update();
+
+        b.iter(|| app.update());
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, ring_benchmark);
+criterion_main!(benches);
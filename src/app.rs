@@ -1,13 +1,10 @@
-use bevy::{
-    prelude::*,
-    window::PrimaryWindow,
-};
+use bevy::{prelude::*, window::PrimaryWindow};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
 #[cfg(not(target_arch = "wasm32"))]
-use bevy::window::WindowMode;
-#[cfg(not(target_arch = "wasm32"))]
-use serde::Deserialize;
-#[cfg(not(target_arch = "wasm32"))]
-use std::fs;
+use std::{fs, io::Write};
+
+use crate::{daily_challenge::ChallengeDate, game::Player};
 
 pub static APP_NAME: &str = "vetovoima";
 pub const PIXELS_PER_METER: f32 = 18.0;
@@ -22,46 +19,190 @@ pub enum AppState {
     GameOver,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-#[derive(Deserialize, Debug)]
-pub struct VVConfig {
-    pub window_mode: WindowMode,
-    pub window_height_pixels: Option<u32>,
-    pub window_width_pixels: Option<u32>,
+// Time trial levels are always the same, in this order
+pub const TIME_TRIAL_LEVEL_SEEDS: [u64; 5] = [
+    0x5EED_0001,
+    0x5EED_0002,
+    0x5EED_0003,
+    0x5EED_0004,
+    0x5EED_0005,
+];
+
+// A versus match is played over a fixed amount of levels (rounds)
+pub const VERSUS_ROUNDS: u32 = 5;
+
+#[derive(Resource, Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GameMode {
+    // Keep going until the time runs out
+    #[default]
+    Endless,
+    // A fixed sequence of levels, played against the clock
+    TimeTrial,
+    // Like endless, but everyone gets the same levels on the same (UTC) day
+    DailyChallenge(ChallengeDate),
+    // Two local players race to the same flag, sharing the gravity controls
+    Versus,
+    // Like endless, but the levels are derived from the given seed
+    Seeded(u64),
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-impl Default for VVConfig {
-    fn default() -> Self {
-        VVConfig {
-            window_mode: WindowMode::Fullscreen(MonitorSelection::Primary),
-            window_width_pixels: Some(720),
-            window_height_pixels: Some(720),
+impl GameMode {
+    pub fn level_seed(&self, level_n: u32) -> u64 {
+        match self {
+            GameMode::Endless | GameMode::Versus => rand::random(),
+            GameMode::TimeTrial => {
+                let level_index = level_n.saturating_sub(1) as usize;
+                TIME_TRIAL_LEVEL_SEEDS[level_index % TIME_TRIAL_LEVEL_SEEDS.len()]
+            }
+            GameMode::DailyChallenge(date) => mix_seed(date.seed().wrapping_add(level_n as u64)),
+            GameMode::Seeded(seed) => mix_seed(seed.wrapping_add(level_n as u64)),
+        }
+    }
+
+    pub fn final_level(&self) -> Option<u32> {
+        match self {
+            GameMode::Endless | GameMode::DailyChallenge(_) | GameMode::Seeded(_) => None,
+            GameMode::TimeTrial => Some(TIME_TRIAL_LEVEL_SEEDS.len() as u32),
+            GameMode::Versus => Some(VERSUS_ROUNDS),
+        }
+    }
+
+    pub fn players(&self) -> &'static [Player] {
+        match self {
+            GameMode::Versus => &[Player::One, Player::Two],
+            _ => &[Player::One],
         }
     }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-fn get_config() -> Result<VVConfig, Box<dyn std::error::Error>> {
-    let contents = fs::read_to_string("VVConfig.toml")?;
-    let config = toml::from_str(contents.as_str())?;
+// Scrambles the bits of a seed (SplitMix64) so that consecutive values produce unrelated seeds
+pub fn mix_seed(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
 
-    Ok(config)
+    z ^ (z >> 31)
 }
 
+// A save file in the working directory (where the older versions saved everything) wins,
+// otherwise the save files go to the platform config directory, next to the config
 #[cfg(not(target_arch = "wasm32"))]
-pub fn get_config_or_default() -> VVConfig {
-    match get_config() {
-        Ok(config) => config,
+pub fn save_file_path(filename: &str) -> PathBuf {
+    let working_directory_path = PathBuf::from(filename);
+
+    if working_directory_path.exists() {
+        return working_directory_path;
+    }
+
+    dirs::config_dir()
+        .map(|config_dir| config_dir.join(APP_NAME).join(filename))
+        .unwrap_or(working_directory_path)
+}
+
+// The browser storage has no directories, the file name is the key
+#[cfg(target_arch = "wasm32")]
+pub fn save_file_path(filename: &str) -> PathBuf {
+    PathBuf::from(filename)
+}
+
+pub fn read_save_file<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    // A missing file is fine (nothing has been saved yet)
+    let contents = read_save_file_contents(path)?;
+
+    match toml::from_str(contents.as_str()) {
+        Ok(data) => Some(data),
         Err(err) => {
-            eprintln!("Could not read or parse VVConfig.toml, falling back to default config");
+            eprintln!("Could not parse {}, ignoring it", path.display());
             eprintln!("Error: {}", err);
 
-            VVConfig::default()
+            None
         }
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_save_file_contents(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_save_file<T: Serialize>(path: &Path, data: &T) {
+    create_parent_directory(path);
+
+    let result = toml::to_string(data)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| fs::write(path, contents).map_err(|err| err.to_string()));
+
+    if let Err(err) = result {
+        eprintln!("Could not write {}", path.display());
+        eprintln!("Error: {}", err);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn append_log_line(path: &Path, line: &str) {
+    create_parent_directory(path);
+
+    let result = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line));
+
+    if let Err(err) = result {
+        eprintln!("Could not write to {}", path.display());
+        eprintln!("Error: {}", err);
+    }
+}
+
+// The platform config directory doesn't exist before the first save
+#[cfg(not(target_arch = "wasm32"))]
+fn create_parent_directory(path: &Path) {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        if let Err(err) = fs::create_dir_all(parent) {
+            eprintln!("Could not create {}", parent.display());
+            eprintln!("Error: {}", err);
+        }
+    }
+}
+
+// There's no file system on the web, the browser storage is used instead (file name as the key)
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read_save_file_contents(path: &Path) -> Option<String> {
+    local_storage()?
+        .get_item(&path.to_string_lossy())
+        .ok()
+        .flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write_save_file<T: Serialize>(path: &Path, data: &T) {
+    let result = toml::to_string(data)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| {
+            local_storage()
+                .ok_or_else(|| "Browser storage is not available".to_string())?
+                .set_item(&path.to_string_lossy(), &contents)
+                .map_err(|err| format!("{:?}", err))
+        });
+
+    if let Err(err) = result {
+        eprintln!("Could not write {}", path.display());
+        eprintln!("Error: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn append_log_line(_path: &Path, _line: &str) {}
+
 #[derive(Component, Clone, Debug, Default, Resource)]
 pub struct ButtonPress {
     pub select_pressed: bool,
@@ -73,6 +214,10 @@ pub struct ButtonPress {
     pub down_pressed: bool,
 }
 
+// The second set of keys, only read in versus mode
+#[derive(Clone, Debug, Default, Resource)]
+pub struct PlayerTwoButtonPress(pub ButtonPress);
+
 pub enum VetovoimaColor {}
 
 impl VetovoimaColor {
@@ -84,6 +229,10 @@ impl VetovoimaColor {
     pub const REDDISH: Color = Color::hsl(10.0, 1.0, 0.66);
     pub const YELLOWISH: Color = Color::hsl(50.0, 1.0, 0.66);
     pub const GREENISH: Color = Color::hsl(150.0, 1.0, 0.66);
+    pub const PINKISH: Color = Color::hsl(320.0, 1.0, 0.72);
+    // The colorblind palette uses these in place of green and red
+    pub const CYANISH: Color = Color::hsl(190.0, 1.0, 0.66);
+    pub const ORANGEISH: Color = Color::hsl(35.0, 1.0, 0.55);
 }
 
 #[derive(Resource)]
@@ -141,6 +290,22 @@ impl UiConfig {
     }
 }
 
+impl UiConfig {
+    // The UI scale setting of VVConfig
+    pub fn with_user_scale(self, user_scale: f32) -> Self {
+        UiConfig {
+            font_size_screen_title: self.font_size_screen_title * user_scale,
+            font_size_body_small: self.font_size_body_small * user_scale,
+            font_size_countdown: self.font_size_countdown * user_scale,
+            font_size_countdown_large: self.font_size_countdown_large * user_scale,
+            font_size_menu_item: self.font_size_menu_item * user_scale,
+            font_size_app_title: self.font_size_app_title * user_scale,
+            scale_multiplier: self.scale_multiplier * user_scale,
+            ..self
+        }
+    }
+}
+
 pub fn cursor_visible<const VISIBILITY: bool>(mut window: Query<&mut Window, With<PrimaryWindow>>) {
     let mut window = window.get_single_mut().unwrap();
 
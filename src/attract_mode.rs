@@ -0,0 +1,140 @@
+use bevy::{
+    ecs::system::SystemParam,
+    input::{keyboard::KeyboardInput, mouse::MouseButtonInput},
+    prelude::*,
+    window::CursorMoved,
+};
+use std::time::Duration;
+
+use crate::{
+    app::{AppState, ButtonPress, GameMode, UiConfig, VetovoimaColor},
+    bot::BotDriver,
+    game::GameLevel,
+    main_menu::spawn_menu,
+};
+
+const MENU_IDLE_SECONDS: u64 = 10;
+const DEMO_MENU_ALPHA: f32 = 0.6;
+
+// Present while the bot plays a demo behind the menu
+#[derive(Resource)]
+pub struct AttractMode;
+
+#[derive(Resource)]
+struct MenuIdleTimer(Timer);
+
+#[derive(Component)]
+struct DemoMenu;
+
+#[derive(SystemParam)]
+struct AnyInput<'w, 's> {
+    keyboard_events: EventReader<'w, 's, KeyboardInput>,
+    mouse_button_events: EventReader<'w, 's, MouseButtonInput>,
+    cursor_events: EventReader<'w, 's, CursorMoved>,
+}
+
+impl AnyInput<'_, '_> {
+    fn received(&mut self) -> bool {
+        // Every reader is drained, so that old input doesn't count later on
+        let keyboard_input = self.keyboard_events.read().count() > 0;
+        let mouse_button_input = self.mouse_button_events.read().count() > 0;
+        let cursor_input = self.cursor_events.read().count() > 0;
+
+        keyboard_input || mouse_button_input || cursor_input
+    }
+}
+
+pub struct AttractModePlugin;
+
+impl Plugin for AttractModePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MenuIdleTimer(Timer::new(
+            Duration::from_secs(MENU_IDLE_SECONDS),
+            TimerMode::Once,
+        )))
+        .add_systems(OnEnter(AppState::InMenu), (reset_idle_timer, end_demo))
+        .add_systems(
+            Update,
+            begin_demo_when_idle
+                .run_if(in_state(AppState::InMenu))
+                .run_if(not(resource_exists::<AttractMode>)),
+        )
+        .add_systems(
+            Update,
+            leave_demo_on_input.run_if(resource_exists::<AttractMode>),
+        );
+    }
+}
+
+fn reset_idle_timer(mut idle_timer: ResMut<MenuIdleTimer>) {
+    idle_timer.0.reset();
+}
+
+fn begin_demo_when_idle(
+    mut commands: Commands,
+    mut idle_timer: ResMut<MenuIdleTimer>,
+    mut any_input: AnyInput,
+    mut app_state: ResMut<NextState<AppState>>,
+    asset_server: Res<AssetServer>,
+    ui_config: Res<UiConfig>,
+    time: Res<Time>,
+) {
+    if any_input.received() {
+        idle_timer.0.reset();
+        return;
+    }
+
+    idle_timer.0.tick(time.delta());
+
+    if !idle_timer.0.finished() {
+        return;
+    }
+
+    // The demo is a regular endless game, played by the bot
+    commands.insert_resource(AttractMode);
+    commands.insert_resource(BotDriver::default());
+    commands.insert_resource(GameMode::Endless);
+    commands.remove_resource::<GameLevel>();
+    app_state.set(AppState::LoadingLevel);
+
+    let font = asset_server.load(ui_config.font_filename);
+    let demo_menu = spawn_menu(
+        &mut commands,
+        font,
+        &ui_config,
+        VetovoimaColor::BLACKISH.with_alpha(DEMO_MENU_ALPHA),
+        false,
+    );
+
+    // Shown on top of the loading and game over screens, too
+    commands
+        .entity(demo_menu)
+        .insert((DemoMenu, GlobalZIndex(2)));
+}
+
+fn leave_demo_on_input(mut any_input: AnyInput, mut app_state: ResMut<NextState<AppState>>) {
+    if any_input.received() {
+        app_state.set(AppState::InMenu);
+    }
+}
+
+fn end_demo(
+    mut commands: Commands,
+    mut button_press: ResMut<ButtonPress>,
+    attract_mode: Option<Res<AttractMode>>,
+    demo_menu_query: Query<Entity, With<DemoMenu>>,
+) {
+    if attract_mode.is_none() {
+        return;
+    }
+
+    commands.remove_resource::<AttractMode>();
+    commands.remove_resource::<BotDriver>();
+    commands.remove_resource::<GameLevel>();
+    // The bot leaves its buttons pressed
+    *button_press = ButtonPress::default();
+
+    for demo_menu in demo_menu_query.iter() {
+        commands.entity(demo_menu).despawn_recursive();
+    }
+}
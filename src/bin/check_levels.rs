@@ -0,0 +1,145 @@
+use std::{env, process::ExitCode};
+
+use vetovoima::{
+    game::level_countdown_secs,
+    level_check::{check_level, LevelCheck, LevelCheckOutcome},
+};
+
+const USAGE: &str = "Usage: check_levels [--seeds N] [--levels N] [--first-seed N] [--help]
+
+Plays the first levels of N seeds (in order, starting from the first seed) with
+a scripted bot and reports the levels where the flag could not be reached in time.
+The bot's solve times per level are compared to the level countdown.";
+
+struct CheckOptions {
+    seeds: u64,
+    levels: u32,
+    first_seed: u64,
+    help: bool,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("{}", USAGE);
+
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if options.help {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let mut level_checks: Vec<LevelCheck> = Vec::new();
+
+    for seed in options.first_seed..options.first_seed + options.seeds {
+        for level_n in 1..=options.levels {
+            let level_check = check_level(seed, level_n);
+
+            println!("{}", level_check);
+            level_checks.push(level_check);
+        }
+    }
+
+    println!();
+
+    for level_n in 1..=options.levels {
+        print_level_summary(level_n, &level_checks);
+    }
+
+    let checked_amount = level_checks.len();
+    let unsolved_levels: Vec<&LevelCheck> = level_checks
+        .iter()
+        .filter(|level_check| level_check.outcome == LevelCheckOutcome::Unsolved)
+        .collect();
+
+    println!(
+        "\n{} of {} levels could not be solved",
+        unsolved_levels.len(),
+        checked_amount
+    );
+
+    for level_check in unsolved_levels.iter() {
+        println!("{}", level_check);
+    }
+
+    if unsolved_levels.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+// Helps to calibrate the countdown of each level
+fn print_level_summary(level_n: u32, level_checks: &[LevelCheck]) {
+    let level_n_checks = level_checks
+        .iter()
+        .filter(|level_check| level_check.level_n == level_n);
+    let solve_times: Vec<f32> = level_n_checks
+        .clone()
+        .filter_map(|level_check| match level_check.outcome {
+            LevelCheckOutcome::Solved(secs) => Some(secs),
+            LevelCheckOutcome::Unsolved => None,
+        })
+        .collect();
+    let checked_amount = level_n_checks.count();
+    let countdown_secs = level_countdown_secs(level_n);
+
+    if solve_times.is_empty() {
+        println!(
+            "level {}: countdown {}s, solved 0/{}",
+            level_n, countdown_secs, checked_amount
+        );
+        return;
+    }
+
+    let mean_secs = solve_times.iter().sum::<f32>() / solve_times.len() as f32;
+    let max_secs = solve_times.iter().copied().fold(0.0, f32::max);
+
+    println!(
+        "level {}: countdown {}s, solved {}/{}, mean {:.1}s, max {:.1}s",
+        level_n,
+        countdown_secs,
+        solve_times.len(),
+        checked_amount,
+        mean_secs,
+        max_secs
+    );
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<CheckOptions, String> {
+    let mut options = CheckOptions {
+        seeds: 20,
+        levels: 3,
+        first_seed: 0,
+        help: false,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "--seeds" => options.seeds = parse_number(&arg, value()?)?,
+            "--levels" => options.levels = parse_number(&arg, value()?)?,
+            "--first-seed" => options.first_seed = parse_number(&arg, value()?)?,
+            "--help" | "-h" => options.help = true,
+
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(arg: &str, value: String) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", arg, value))
+}
@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use std::f32::consts::TAU;
+
+use crate::{
+    app::{AppState, ButtonPress, PIXELS_PER_METER},
+    game::{Flag, Player},
+    simulation::GravitySource,
+    tuning::Tuning,
+};
+
+// Below this forward speed (pixels per second) the player is considered to be stuck
+const BOT_STUCK_SPEED: f32 = 10.0;
+const BOT_STUCK_SECONDS: f32 = 1.0;
+// Debris right in front of the player is lifted sooner, before it brings the player to a halt
+const BOT_STUCK_SECONDS_DEBRIS_AHEAD: f32 = 0.4;
+const BOT_DEBRIS_LOOKAHEAD_METERS: f32 = 2.5;
+const BOT_DEBRIS_SCAN_RADIUS_METERS: f32 = 1.5;
+// The player drives a lot faster when the gravity doesn't press it against the terrain. The
+// gravity changes in steps, so it is only lowered once it's this much above the driving force.
+const BOT_DRIVE_GRAVITY_FORCE: f32 = 0.15;
+const BOT_DRIVE_GRAVITY_MARGIN: f32 = 0.02;
+// Gravity is briefly reversed (all the way) to lift the debris (and the player) out of the way
+const BOT_MAX_LIFT_SECONDS: f32 = 1.0;
+// Lifting right next to the flag would only carry the player away from it
+const BOT_NO_LIFT_FLAG_ANGLE: f32 = 0.1;
+
+// What the bot knows about the game at a point in time
+#[derive(Clone, Copy, Debug)]
+pub struct BotObservation {
+    pub player_position: Vec2,
+    pub player_forward: Vec2,
+    pub player_velocity: Vec2,
+    pub gravity_force: f32,
+    pub max_gravity_force: f32,
+    pub flag_position: Vec2,
+    // How far the flag is along the circle in the direction the player is facing (radians, 0..TAU)
+    pub flag_angle_ahead: f32,
+    // Debris (any dynamic object that isn't a player) in front of the player
+    pub debris_ahead: usize,
+}
+
+impl BotObservation {
+    pub fn observe(world: &mut World, player: Player) -> Option<Self> {
+        world
+            .run_system_cached_with(observe_player, player)
+            .ok()
+            .flatten()
+    }
+
+    pub fn forward_speed(&self) -> f32 {
+        self.player_forward.dot(self.player_velocity)
+    }
+}
+
+pub fn observe_player(
+    In(player): In<Player>,
+    player_query: Query<(Entity, &Transform, &Velocity, &Player)>,
+    flag_query: Query<&Transform, With<Flag>>,
+    gravity_source: Res<GravitySource>,
+    tuning: Res<Tuning>,
+    rapier_context: ReadDefaultRapierContext,
+) -> Option<BotObservation> {
+    let flag_position = flag_query.get_single().ok()?.translation.truncate();
+    let (_, player_transform, player_velocity, _) =
+        player_query.iter().find(|(_, _, _, p)| **p == player)?;
+    let player_position = player_transform.translation.truncate();
+    let player_forward = player_transform.local_x().truncate();
+
+    // The counterclockwise tangent of the circle tells which way around the player is going
+    let counterclockwise = player_position.perp().normalize_or_zero();
+    let direction = if player_forward.dot(counterclockwise) >= 0.0 {
+        1.0
+    } else {
+        -1.0
+    };
+    let flag_angle_ahead = (player_position.angle_to(flag_position) * direction).rem_euclid(TAU);
+
+    let player_entities: Vec<Entity> = player_query.iter().map(|(entity, ..)| entity).collect();
+    let scan_position =
+        player_position + player_forward * BOT_DEBRIS_LOOKAHEAD_METERS * PIXELS_PER_METER;
+    let scan_shape = Collider::ball(BOT_DEBRIS_SCAN_RADIUS_METERS * PIXELS_PER_METER);
+    let mut debris_ahead = 0;
+
+    rapier_context.intersections_with_shape(
+        scan_position,
+        0.0,
+        &scan_shape,
+        QueryFilter::predicate(QueryFilter::only_dynamic(), &|entity: Entity| {
+            !player_entities.contains(&entity)
+        }),
+        |_| {
+            debris_ahead += 1;
+
+            true
+        },
+    );
+
+    Some(BotObservation {
+        player_position,
+        player_forward,
+        player_velocity: player_velocity.linvel,
+        gravity_force: gravity_source.force,
+        max_gravity_force: tuning.max_gravity_force,
+        flag_position,
+        flag_angle_ahead,
+        debris_ahead,
+    })
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum BotPhase {
+    #[default]
+    Driving,
+    Lifting,
+    Landing,
+}
+
+// Drives forward around the circle at a weak gravity, and reverses the gravity for a moment
+// whenever debris gets in the way
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedBot {
+    phase: BotPhase,
+    phase_secs: f32,
+    stuck_secs: f32,
+}
+
+impl ScriptedBot {
+    pub fn next_button_press(
+        &mut self,
+        observation: &BotObservation,
+        delta_secs: f32,
+    ) -> ButtonPress {
+        self.phase_secs += delta_secs;
+
+        match self.phase {
+            BotPhase::Driving => {
+                let max_stuck_secs = if observation.debris_ahead > 0 {
+                    BOT_STUCK_SECONDS_DEBRIS_AHEAD
+                } else {
+                    BOT_STUCK_SECONDS
+                };
+                let is_near_flag = observation.flag_angle_ahead < BOT_NO_LIFT_FLAG_ANGLE;
+
+                if observation.forward_speed() < BOT_STUCK_SPEED {
+                    self.stuck_secs += delta_secs;
+                } else {
+                    self.stuck_secs = 0.0;
+                }
+
+                if self.stuck_secs > max_stuck_secs && !is_near_flag {
+                    self.enter_phase(BotPhase::Lifting);
+                }
+            }
+            BotPhase::Lifting => {
+                if observation.gravity_force <= -observation.max_gravity_force
+                    || self.phase_secs > BOT_MAX_LIFT_SECONDS
+                {
+                    self.enter_phase(BotPhase::Landing);
+                }
+            }
+            BotPhase::Landing => {
+                if observation.gravity_force >= BOT_DRIVE_GRAVITY_FORCE {
+                    self.enter_phase(BotPhase::Driving);
+                }
+            }
+        }
+
+        let is_gravity_too_strong = self.phase == BotPhase::Driving
+            && observation.gravity_force > BOT_DRIVE_GRAVITY_FORCE + BOT_DRIVE_GRAVITY_MARGIN;
+
+        ButtonPress {
+            right_pressed: true,
+            up_pressed: self.phase == BotPhase::Lifting || is_gravity_too_strong,
+            down_pressed: self.phase == BotPhase::Landing,
+            ..default()
+        }
+    }
+
+    fn enter_phase(&mut self, phase: BotPhase) {
+        self.phase = phase;
+        self.phase_secs = 0.0;
+        self.stuck_secs = 0.0;
+    }
+}
+
+// When present, the bot plays the game in place of the (first) player
+#[derive(Resource, Default)]
+pub struct BotDriver(pub ScriptedBot);
+
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        // The input is decided before the game systems run
+        app.add_systems(
+            PreUpdate,
+            drive_button_press
+                .run_if(in_state(AppState::InGame))
+                .run_if(resource_exists::<BotDriver>),
+        )
+        .add_systems(OnEnter(AppState::InGame), reset_bot);
+    }
+}
+
+fn reset_bot(bot_driver: Option<ResMut<BotDriver>>) {
+    if let Some(mut bot_driver) = bot_driver {
+        bot_driver.0 = ScriptedBot::default();
+    }
+}
+
+fn drive_button_press(world: &mut World) {
+    let Some(observation) = BotObservation::observe(world, Player::One) else {
+        return;
+    };
+    let delta_secs = world.resource::<Time>().delta_secs();
+    let next_button_press = world
+        .resource_mut::<BotDriver>()
+        .0
+        .next_button_press(&observation, delta_secs);
+
+    *world.resource_mut::<ButtonPress>() = next_button_press;
+}
@@ -0,0 +1,128 @@
+use bevy::window::{MonitorSelection, WindowMode};
+use std::path::PathBuf;
+
+use crate::{app::GameMode, config::VVConfig};
+
+pub const USAGE: &str = "Usage: vetovoima [OPTIONS]
+
+Options:
+  --config <path>       Read the config from (and save it to) the given file
+  --level <n>           Start a game right away, from level n
+  --seed <n>            Start a seeded game right away (decimal, or hex with 0x)
+  --window-mode <mode>  windowed, borderless or fullscreen
+  --headless            Let the bot play without a window or audio, and print the results
+  --replay <path>       Start a time trial against the ghost saved in the given file
+  --mute                Turn off all sounds
+  --dev-tools           Show the dev tools
+  --no-dev-tools        Hide the dev tools, even if DEV_TOOLS=1 is set
+  --help                Show this message";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CliOptions {
+    pub config_path: Option<PathBuf>,
+    pub level: Option<u32>,
+    pub seed: Option<u64>,
+    pub window_mode: Option<WindowMode>,
+    pub headless: bool,
+    pub replay_path: Option<PathBuf>,
+    pub mute: bool,
+    // Unset means that the DEV_TOOLS environment variable decides
+    pub dev_tools: Option<bool>,
+    pub help: bool,
+}
+
+impl CliOptions {
+    // The command line wins over the config
+    pub fn apply_to_config(&self, config: &mut VVConfig) {
+        if let Some(window_mode) = self.window_mode {
+            config.window_mode = window_mode;
+        }
+
+        if self.mute {
+            config.main_volume = 0.0;
+            config.effects_volume = 0.0;
+        }
+    }
+
+    // The game to start right away (instead of showing the menu), if any
+    pub fn game_mode(&self) -> Option<GameMode> {
+        if self.replay_path.is_some() {
+            Some(GameMode::TimeTrial)
+        } else if let Some(seed) = self.seed {
+            Some(GameMode::Seeded(seed))
+        } else if self.level.is_some() || self.headless {
+            Some(GameMode::Endless)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn parse_args(mut args: impl Iterator<Item = String>) -> Result<CliOptions, String> {
+    let mut options = CliOptions::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "--config" => options.config_path = Some(PathBuf::from(value()?)),
+            "--level" => options.level = Some(parse_level(value()?)?),
+            "--seed" => options.seed = Some(parse_seed(value()?)?),
+            "--window-mode" => options.window_mode = Some(parse_window_mode(value()?)?),
+            "--headless" => options.headless = true,
+            "--replay" => options.replay_path = Some(PathBuf::from(value()?)),
+            "--mute" => options.mute = true,
+            "--dev-tools" => options.dev_tools = Some(true),
+            "--no-dev-tools" => options.dev_tools = Some(false),
+            "--help" | "-h" => options.help = true,
+
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    // Time trial levels are always the same, so the replay can't be combined with these
+    if options.replay_path.is_some() && (options.level.is_some() || options.seed.is_some()) {
+        return Err("--replay can't be used with --level or --seed".to_string());
+    }
+
+    if options.replay_path.is_some() && options.headless {
+        return Err("--replay can't be used with --headless".to_string());
+    }
+
+    Ok(options)
+}
+
+fn parse_level(value: String) -> Result<u32, String> {
+    match value.parse() {
+        Ok(level) if level >= 1 => Ok(level),
+        _ => Err(format!(
+            "Invalid value for --level: {} (levels start from 1)",
+            value
+        )),
+    }
+}
+
+fn parse_seed(value: String) -> Result<u64, String> {
+    let seed = match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+
+    seed.map_err(|_| format!("Invalid value for --seed: {}", value))
+}
+
+fn parse_window_mode(value: String) -> Result<WindowMode, String> {
+    match value.as_str() {
+        "windowed" => Ok(WindowMode::Windowed),
+        "borderless" => Ok(WindowMode::BorderlessFullscreen(MonitorSelection::Primary)),
+        "fullscreen" => Ok(WindowMode::Fullscreen(MonitorSelection::Primary)),
+
+        _ => Err(format!(
+            "Invalid value for --window-mode: {} (windowed, borderless or fullscreen)",
+            value
+        )),
+    }
+}
@@ -0,0 +1,253 @@
+use bevy::{prelude::*, window::WindowMode};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::app::{read_save_file_contents, save_file_path, write_save_file};
+
+pub const CONFIG_FILENAME: &str = "VVConfig.toml";
+// Bump this when the format changes, and add a migration from the previous version
+pub const CONFIG_VERSION: i64 = 2;
+const MIN_WINDOW_SIZE_PIXELS: u32 = 240;
+const MAX_WINDOW_SIZE_PIXELS: u32 = 7680;
+const MIN_UI_SCALE: f32 = 0.5;
+const MAX_UI_SCALE: f32 = 3.0;
+
+// Player one's keys (player two gets the other set in versus mode)
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ControlLayout {
+    #[default]
+    Arrows,
+    Wasd,
+}
+
+// Missing values fall back to the defaults, so a config file only needs the changed values
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct VVConfig {
+    pub version: i64,
+    // The window settings have no effect on the web
+    pub window_mode: WindowMode,
+    pub window_height_pixels: Option<u32>,
+    pub window_width_pixels: Option<u32>,
+    // The master volume, the effects volume is relative to it
+    pub main_volume: f64,
+    pub effects_volume: f64,
+    // Applied on top of the scaling that follows the window size
+    pub ui_scale: f32,
+    pub colorblind_palette: bool,
+    pub controls: ControlLayout,
+}
+
+impl Default for VVConfig {
+    fn default() -> Self {
+        VVConfig {
+            version: CONFIG_VERSION,
+            window_mode: WindowMode::Fullscreen(MonitorSelection::Primary),
+            window_width_pixels: Some(720),
+            window_height_pixels: Some(720),
+            main_volume: 1.0,
+            effects_volume: 1.0,
+            ui_scale: 1.0,
+            colorblind_palette: false,
+            controls: ControlLayout::Arrows,
+        }
+    }
+}
+
+impl VVConfig {
+    pub fn effects_output_volume(&self) -> f64 {
+        self.main_volume * self.effects_volume
+    }
+
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        let window_size_range = MIN_WINDOW_SIZE_PIXELS..=MAX_WINDOW_SIZE_PIXELS;
+
+        for (field, size) in [
+            ("window_width_pixels", self.window_width_pixels),
+            ("window_height_pixels", self.window_height_pixels),
+        ] {
+            if let Some(size) = size.filter(|size| !window_size_range.contains(size)) {
+                errors.push(ConfigError::new(
+                    field,
+                    format!(
+                        "{} is out of range ({}-{})",
+                        size, MIN_WINDOW_SIZE_PIXELS, MAX_WINDOW_SIZE_PIXELS
+                    ),
+                ));
+            }
+        }
+
+        for (field, volume) in [
+            ("main_volume", self.main_volume),
+            ("effects_volume", self.effects_volume),
+        ] {
+            if !(0.0..=1.0).contains(&volume) {
+                errors.push(ConfigError::new(
+                    field,
+                    format!("{} is out of range (0.0-1.0)", volume),
+                ));
+            }
+        }
+
+        if !(MIN_UI_SCALE..=MAX_UI_SCALE).contains(&self.ui_scale) {
+            errors.push(ConfigError::new(
+                "ui_scale",
+                format!(
+                    "{} is out of range ({}-{})",
+                    self.ui_scale, MIN_UI_SCALE, MAX_UI_SCALE
+                ),
+            ));
+        }
+
+        errors
+    }
+}
+
+// A problem with the config, the field is empty if the problem isn't about a single value
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigError {
+    pub field: String,
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        ConfigError {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.field, self.message)
+        }
+    }
+}
+
+// Where the config is read from and saved to
+#[derive(Resource, Clone, Debug)]
+pub struct ConfigPath(pub PathBuf);
+
+// The config as it is in the file, without the command line overrides (e.g. `--mute`) that
+// only last for the session
+#[derive(Resource, Clone, Debug)]
+pub struct SavedConfig(pub VVConfig);
+
+// The path given on the command line wins, then a config in the working directory, and
+// finally the platform config directory (e.g. ~/.config/vetovoima on Linux)
+pub fn find_config_path(path_override: Option<PathBuf>) -> PathBuf {
+    path_override.unwrap_or_else(|| save_file_path(CONFIG_FILENAME))
+}
+
+// A missing config is not an error (Ok(None)), the defaults are used then
+pub fn load_config(path: &Path) -> Result<Option<VVConfig>, Vec<ConfigError>> {
+    match read_save_file_contents(path) {
+        Some(contents) => parse_config(&contents).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub fn save_config(path: &Path, config: &VVConfig) {
+    write_save_file(path, config);
+}
+
+// Older versions are migrated to the current one, then every value is checked. All the
+// problems are reported at once.
+pub fn parse_config(contents: &str) -> Result<VVConfig, Vec<ConfigError>> {
+    let mut table: toml::Table =
+        toml::from_str(contents).map_err(|err| vec![ConfigError::new("", err.to_string())])?;
+
+    migrate_config(&mut table)?;
+
+    let Ok(toml::Value::Table(defaults)) = toml::Value::try_from(VVConfig::default()) else {
+        unreachable!("The default config is a table");
+    };
+    let mut valid_table = defaults.clone();
+    let mut errors = Vec::new();
+
+    // Each value is tried on its own, so that every broken value gets its own message
+    for (field, value) in table {
+        if !defaults.contains_key(&field) {
+            errors.push(ConfigError::new(&field, "unknown setting"));
+            continue;
+        }
+
+        let mut single_value_table = defaults.clone();
+        single_value_table.insert(field.clone(), value.clone());
+
+        match toml::Value::Table(single_value_table).try_into::<VVConfig>() {
+            Ok(_) => {
+                valid_table.insert(field, value);
+            }
+            Err(err) => errors.push(ConfigError::new(&field, err.message())),
+        }
+    }
+
+    let config: VVConfig = toml::Value::Table(valid_table)
+        .try_into()
+        .map_err(|err| vec![ConfigError::new("", err.message())])?;
+
+    errors.extend(config.validate());
+
+    if errors.is_empty() {
+        Ok(config)
+    } else {
+        Err(errors)
+    }
+}
+
+fn migrate_config(table: &mut toml::Table) -> Result<(), Vec<ConfigError>> {
+    // The first version had no version field
+    let version = match table.get("version") {
+        None => 1,
+        Some(toml::Value::Integer(version)) => *version,
+        Some(_) => return Err(vec![ConfigError::new("version", "must be a number")]),
+    };
+
+    if !(1..=CONFIG_VERSION).contains(&version) {
+        return Err(vec![ConfigError::new(
+            "version",
+            format!(
+                "{} is not supported (the latest version is {})",
+                version, CONFIG_VERSION
+            ),
+        )]);
+    }
+
+    if version < 2 {
+        migrate_from_version_1(table);
+    }
+
+    table.insert("version".to_string(), toml::Value::Integer(CONFIG_VERSION));
+
+    Ok(())
+}
+
+// The fullscreen window modes used to be plain names, now they include the monitor
+fn migrate_from_version_1(table: &mut toml::Table) {
+    let Some(toml::Value::String(window_mode)) = table.get("window_mode") else {
+        return;
+    };
+
+    if matches!(
+        window_mode.as_str(),
+        "Fullscreen" | "BorderlessFullscreen" | "SizedFullscreen"
+    ) {
+        let mut monitor = toml::Table::new();
+
+        monitor.insert(
+            window_mode.clone(),
+            toml::Value::String("Primary".to_string()),
+        );
+        table.insert("window_mode".to_string(), toml::Value::Table(monitor));
+    }
+}
@@ -0,0 +1,277 @@
+use bevy::{prelude::*, utils::SystemTime};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{
+    app::{
+        mix_seed, read_save_file, save_file_path, write_save_file, AppState, GameMode, UiConfig,
+        VetovoimaColor,
+    },
+    stats::RunStats,
+};
+
+const DAILY_CHALLENGE_RECORD_FILENAME: &str = "VVDailyChallenge.toml";
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ChallengeDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl ChallengeDate {
+    pub fn today() -> Self {
+        let seconds_since_epoch = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        ChallengeDate::from_days_since_epoch((seconds_since_epoch / SECONDS_PER_DAY) as i64)
+    }
+
+    // Converts days since 1970-01-01 to a (proleptic Gregorian) calendar date
+    // Source: Howard Hinnant's "civil_from_days" algorithm
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // the year starts from March in this calculation
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        ChallengeDate {
+            year: year as i32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        let date_value = self.year as u64 * 10_000 + self.month as u64 * 100 + self.day as u64;
+
+        mix_seed(date_value)
+    }
+}
+
+impl fmt::Display for ChallengeDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+// Only the first attempt of each day counts
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+struct DailyChallengeRecord {
+    attempts: Vec<DailyChallengeAttempt>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct DailyChallengeAttempt {
+    date: String,
+    // TOML integers are signed, so the seed is stored as a hex string
+    seed: String,
+    score: u32,
+}
+
+impl DailyChallengeRecord {
+    fn attempt_on(&self, date: &ChallengeDate) -> Option<&DailyChallengeAttempt> {
+        let date_label = date.to_string();
+
+        self.attempts
+            .iter()
+            .find(|attempt| attempt.date == date_label)
+    }
+
+    fn attempt_on_mut(&mut self, date: &ChallengeDate) -> Option<&mut DailyChallengeAttempt> {
+        let date_label = date.to_string();
+
+        self.attempts
+            .iter_mut()
+            .find(|attempt| attempt.date == date_label)
+    }
+}
+
+// When present, the current run is the attempt that counts for the day
+#[derive(Resource)]
+struct CountedAttempt;
+
+#[derive(Component)]
+struct DailyChallengeUI;
+
+#[derive(Component)]
+struct DailyChallengeResultText;
+
+pub struct DailyChallengePlugin;
+
+impl Plugin for DailyChallengePlugin {
+    fn build(&self, app: &mut App) {
+        let record: DailyChallengeRecord =
+            read_save_file(&save_file_path(DAILY_CHALLENGE_RECORD_FILENAME)).unwrap_or_default();
+
+        app.insert_resource(record)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (begin_attempt, hud_setup)
+                    .chain()
+                    .run_if(in_daily_challenge),
+            )
+            .add_systems(OnExit(AppState::InGame), hud_cleanup)
+            .add_systems(
+                OnEnter(AppState::GameOver),
+                record_attempt.run_if(in_daily_challenge),
+            )
+            .add_systems(OnExit(AppState::GameOver), result_cleanup)
+            .add_systems(OnEnter(AppState::InMenu), end_attempt);
+    }
+}
+
+fn in_daily_challenge(game_mode: Res<GameMode>) -> bool {
+    matches!(*game_mode, GameMode::DailyChallenge(_))
+}
+
+// The attempt is recorded (with no score) as soon as the run begins, so that quitting to the
+// menu mid-run still uses up the attempt of the day
+fn begin_attempt(
+    mut commands: Commands,
+    mut record: ResMut<DailyChallengeRecord>,
+    game_mode: Res<GameMode>,
+) {
+    let GameMode::DailyChallenge(date) = *game_mode else {
+        return;
+    };
+
+    if record.attempt_on(&date).is_some() {
+        return;
+    }
+
+    record.attempts.push(DailyChallengeAttempt {
+        date: date.to_string(),
+        seed: format!("{:016x}", date.seed()),
+        score: 0,
+    });
+    write_save_file(&save_file_path(DAILY_CHALLENGE_RECORD_FILENAME), &*record);
+    commands.insert_resource(CountedAttempt);
+}
+
+fn end_attempt(mut commands: Commands) {
+    commands.remove_resource::<CountedAttempt>();
+}
+
+fn hud_setup(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    record: Res<DailyChallengeRecord>,
+    counted_attempt: Option<Res<CountedAttempt>>,
+    asset_server: Res<AssetServer>,
+    ui_config: Res<UiConfig>,
+) {
+    let GameMode::DailyChallenge(date) = *game_mode else {
+        return;
+    };
+    let font = asset_server.load(ui_config.font_filename);
+    let attempt_label = if counted_attempt.is_none() && record.attempt_on(&date).is_some() {
+        " (practice)"
+    } else {
+        ""
+    };
+
+    commands.spawn((
+        Text::new(format!(
+            "Daily {} seed {:016x}{}",
+            date,
+            date.seed(),
+            attempt_label
+        )),
+        TextFont {
+            font: font.clone(),
+            font_size: ui_config.font_size_body_small,
+            ..Default::default()
+        },
+        TextColor(VetovoimaColor::WHITEISH),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        },
+        DailyChallengeUI,
+    ));
+}
+
+fn hud_cleanup(mut commands: Commands, ui_query: Query<Entity, With<DailyChallengeUI>>) {
+    for ui_entity in ui_query.iter() {
+        commands.entity(ui_entity).despawn_recursive();
+    }
+}
+
+fn record_attempt(
+    mut commands: Commands,
+    mut record: ResMut<DailyChallengeRecord>,
+    game_mode: Res<GameMode>,
+    run_stats: Res<RunStats>,
+    counted_attempt: Option<Res<CountedAttempt>>,
+    asset_server: Res<AssetServer>,
+    ui_config: Res<UiConfig>,
+) {
+    let GameMode::DailyChallenge(date) = *game_mode else {
+        return;
+    };
+    // Scored like on the game over screen, the level that was being played when the time ran
+    // out doesn't count
+    let score = run_stats.score();
+
+    let result_label = match record.attempt_on_mut(&date) {
+        Some(attempt) if counted_attempt.is_some() => {
+            attempt.score = score;
+            write_save_file(&save_file_path(DAILY_CHALLENGE_RECORD_FILENAME), &*record);
+            commands.remove_resource::<CountedAttempt>();
+
+            format!("Daily {} score {} (recorded)", date, score)
+        }
+        Some(attempt) => format!(
+            "Daily {} score {} (recorded score {})",
+            date, score, attempt.score
+        ),
+        None => format!("Daily {} score {}", date, score),
+    };
+
+    let font = asset_server.load(ui_config.font_filename);
+
+    commands.spawn((
+        Text::new(result_label),
+        TextFont {
+            font: font.clone(),
+            font_size: ui_config.font_size_countdown,
+            ..Default::default()
+        },
+        TextColor(VetovoimaColor::YELLOWISH),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(40.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        // Show on top of the game over screen
+        GlobalZIndex(1),
+        DailyChallengeResultText,
+    ));
+}
+
+fn result_cleanup(
+    mut commands: Commands,
+    text_query: Query<Entity, With<DailyChallengeResultText>>,
+) {
+    for text_entity in text_query.iter() {
+        commands.entity(text_entity).despawn_recursive();
+    }
+}
@@ -3,16 +3,52 @@ use std::env;
 use bevy::{
     app::PluginGroupBuilder,
     diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
     prelude::*,
 };
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    app::{UiConfig, VetovoimaColor},
-    game::Player,
-    simulation::GravitySource,
+    app::{AppState, ButtonPress, PlayerTwoButtonPress, UiConfig, VetovoimaColor},
+    game::{CountdownFrozen, DebrisCollisionsOff, GameLevel, LevelJump, NextLevelSeed, Player},
+    profiling::{write_profile_report, ProfilingPlugin, PROFILE_REPORT_FILENAME},
+    simulation::{gravity_force_at, Attractable, GravityAutoCycle, GravitySource},
+    tuning::{run_tuning_command, Tuning, TUNING_COMMANDS_HELP},
 };
 
+const CONSOLE_TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+const CONSOLE_TOGGLE_CHARACTER: &str = "`";
+const CONSOLE_VISIBLE_LINES: usize = 12;
+const CONSOLE_ALPHA: f32 = 0.85;
+
+const CHEAT_SKIP_LEVEL_KEY: KeyCode = KeyCode::F5;
+const CHEAT_FREEZE_COUNTDOWN_KEY: KeyCode = KeyCode::F6;
+const CHEAT_DEBRIS_COLLISIONS_KEY: KeyCode = KeyCode::F7;
+const CHEAT_NEW_SEED_KEY: KeyCode = KeyCode::F8;
+const CHEAT_GRAVITY_AUTO_CYCLE_KEY: KeyCode = KeyCode::F9;
+const FORCE_OVERLAY_KEY: KeyCode = KeyCode::F10;
+const CHEATS_HELP: [&str; 9] = [
+    "level <n>  jump to level n",
+    "F5  skip to the next level",
+    "F6  freeze the countdown",
+    "F7  go through the debris",
+    "F8  regenerate the level with a new seed",
+    "F9  gravity auto-cycle",
+    "F10  force and trajectory overlay",
+    "F11  profiling HUD",
+    "profile [file]  write the timings to VVProfile.txt",
+];
+
+// The forces are huge compared to the size of the world (see `Tuning::gravity_force_scale`)
+const FORCE_GIZMO_SCALE: f32 = 0.0005;
+// The velocity arrow ends where the player would be after this many seconds
+const VELOCITY_GIZMO_SECONDS: f32 = 0.5;
+const TRAJECTORY_SECONDS: f32 = 2.0;
+const TRAJECTORY_STEPS: u32 = 60;
+
 #[derive(Component)]
 struct FpsText;
 
@@ -22,6 +58,29 @@ struct GravityText;
 #[derive(Component)]
 struct PlayerText;
 
+#[derive(Component)]
+struct CheatsText;
+
+// When present, the forces, the player velocity and the predicted trajectory are drawn
+#[derive(Resource, Default)]
+struct ForceOverlay;
+
+// Exists while the dev console is open, the game gets no keyboard input then
+#[derive(Resource, Default)]
+pub struct DevConsole {
+    input: String,
+    lines: Vec<String>,
+}
+
+#[derive(Component)]
+struct DevConsoleUi;
+
+#[derive(Component)]
+struct DevConsoleLinesText;
+
+#[derive(Component)]
+struct DevConsoleInputText;
+
 #[derive(Default)]
 struct DebugOutputPlugin;
 
@@ -34,22 +93,95 @@ impl Plugin for DebugOutputPlugin {
                 fps_text_update,
                 gravity_debug_text_update,
                 player_text_update,
+                cheats_text_update,
             ),
         );
     }
 }
 
-pub struct DevTools;
+// Hotkeys for getting to the later levels quickly, see CHEATS_HELP
+struct DevCheatsPlugin;
+
+impl Plugin for DevCheatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            cheat_hotkeys
+                .run_if(in_state(AppState::InGame))
+                .run_if(not(resource_exists::<DevConsole>)),
+        );
+    }
+}
+
+// Draws what the physics is doing: the gravity pulling each body, where the player is heading
+// and where the gravity alone would take the player (controls and collisions aside)
+struct ForceOverlayPlugin;
+
+impl Plugin for ForceOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            toggle_force_overlay.run_if(not(resource_exists::<DevConsole>)),
+        )
+        .add_systems(
+            Update,
+            (
+                read_player_mass,
+                draw_forces,
+                draw_player_velocity,
+                draw_player_trajectory,
+            )
+                .run_if(in_state(AppState::InGame))
+                .run_if(resource_exists::<ForceOverlay>),
+        );
+    }
+}
+
+// Type "help" in the console (toggled with the key below Esc) for the commands
+struct DevConsolePlugin;
+
+impl Plugin for DevConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                toggle_console,
+                console_input.run_if(resource_exists::<DevConsole>),
+                show_console.run_if(resource_added::<DevConsole>),
+                console_text_update.run_if(resource_changed::<DevConsole>),
+                hide_console.run_if(resource_removed::<DevConsole>),
+            )
+                .chain(),
+        );
+    }
+}
+
+pub struct DevTools {
+    pub enabled: bool,
+}
+
+impl DevTools {
+    // DEV_TOOLS=1 turns the dev tools on
+    pub fn from_env() -> Self {
+        DevTools {
+            enabled: matches!(env::var("DEV_TOOLS"), Result::Ok(value) if value == *"1"),
+        }
+    }
+}
 
 impl PluginGroup for DevTools {
     fn build(self) -> PluginGroupBuilder {
-        match env::var("DEV_TOOLS") {
-            Result::Ok(value) if value == *"1" => PluginGroupBuilder::start::<Self>()
+        if self.enabled {
+            PluginGroupBuilder::start::<Self>()
                 .add(RapierDebugRenderPlugin::default())
                 .add(FrameTimeDiagnosticsPlugin::default())
-                .add(DebugOutputPlugin::default()),
-
-            _ => PluginGroupBuilder::start::<Self>(),
+                .add(DebugOutputPlugin::default())
+                .add(DevConsolePlugin)
+                .add(DevCheatsPlugin)
+                .add(ForceOverlayPlugin)
+                .add(ProfilingPlugin)
+        } else {
+            PluginGroupBuilder::start::<Self>()
         }
     }
 }
@@ -101,6 +233,28 @@ fn debug_setup(mut commands: Commands, asset_server: Res<AssetServer>, ui_config
             GravityText,
         ));
 
+    commands
+        .spawn((
+            Text::new("Cheats "),
+            TextFont {
+                font: font.clone(),
+                font_size: ui_config.font_size_body_small,
+                ..Default::default()
+            },
+            TextColor(VetovoimaColor::WHITEISH),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(82.0),
+                left: Val::Px(10.0),
+                ..default()
+            },
+        ))
+        .with_child((
+            TextSpan::default(),
+            TextColor(VetovoimaColor::GREENISH),
+            CheatsText,
+        ));
+
     commands
         .spawn((
             Text::new("Player velocity "),
@@ -147,18 +301,367 @@ fn gravity_debug_text_update(
 }
 
 fn player_text_update(
-    velocity_query: Query<&Velocity, With<Player>>,
+    velocity_query: Query<(&Velocity, &Player)>,
     mut player_text_query: Query<&mut TextSpan, With<PlayerText>>,
 ) {
-    match velocity_query.get_single() {
-        Err(_) => {}
-        Ok(velocity) => {
-            let mut span = player_text_query.single_mut();
+    let mut velocities: Vec<(&Velocity, &Player)> = velocity_query.iter().collect();
 
-            **span = format!(
+    if velocities.is_empty() {
+        return;
+    }
+
+    // Player one first, then player two in versus mode
+    velocities.sort_by_key(|(_, player)| **player);
+
+    let velocity_labels: Vec<String> = velocities
+        .iter()
+        .map(|(velocity, _)| {
+            format!(
                 "[{:6.1},{:6.1}] / {:4.1}",
                 velocity.linvel.x, velocity.linvel.y, velocity.angvel
-            );
+            )
+        })
+        .collect();
+
+    for mut span in player_text_query.iter_mut() {
+        **span = velocity_labels.join(" | ");
+    }
+}
+
+fn cheats_text_update(
+    mut cheats_text_query: Query<&mut TextSpan, With<CheatsText>>,
+    countdown_frozen: Option<Res<CountdownFrozen>>,
+    debris_collisions_off: Option<Res<DebrisCollisionsOff>>,
+    gravity_auto_cycle: Option<Res<GravityAutoCycle>>,
+) {
+    let cheats: Vec<&str> = [
+        (countdown_frozen.is_some(), "frozen countdown"),
+        (debris_collisions_off.is_some(), "no debris"),
+        (gravity_auto_cycle.is_some(), "auto gravity"),
+    ]
+    .into_iter()
+    .filter_map(|(is_on, label)| is_on.then_some(label))
+    .collect();
+
+    for mut span in cheats_text_query.iter_mut() {
+        **span = if cheats.is_empty() {
+            "none".to_string()
+        } else {
+            cheats.join(", ")
+        };
+    }
+}
+
+fn cheat_hotkeys(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    game_level: Res<GameLevel>,
+    countdown_frozen: Option<Res<CountdownFrozen>>,
+    debris_collisions_off: Option<Res<DebrisCollisionsOff>>,
+    gravity_auto_cycle: Option<Res<GravityAutoCycle>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if keyboard_input.just_pressed(CHEAT_SKIP_LEVEL_KEY) {
+        app_state.set(AppState::LoadingLevel);
+    }
+
+    if keyboard_input.just_pressed(CHEAT_NEW_SEED_KEY) {
+        commands.insert_resource(LevelJump(game_level.n));
+        commands.insert_resource(NextLevelSeed(rand::random()));
+        app_state.set(AppState::LoadingLevel);
+    }
+
+    if keyboard_input.just_pressed(CHEAT_FREEZE_COUNTDOWN_KEY) {
+        toggle_resource(&mut commands, countdown_frozen.is_some(), CountdownFrozen);
+    }
+
+    if keyboard_input.just_pressed(CHEAT_DEBRIS_COLLISIONS_KEY) {
+        toggle_resource(
+            &mut commands,
+            debris_collisions_off.is_some(),
+            DebrisCollisionsOff,
+        );
+    }
+
+    if keyboard_input.just_pressed(CHEAT_GRAVITY_AUTO_CYCLE_KEY) {
+        toggle_resource(
+            &mut commands,
+            gravity_auto_cycle.is_some(),
+            GravityAutoCycle,
+        );
+    }
+}
+
+fn toggle_resource<R: Resource>(commands: &mut Commands, is_on: bool, resource: R) {
+    if is_on {
+        commands.remove_resource::<R>();
+    } else {
+        commands.insert_resource(resource);
+    }
+}
+
+fn toggle_force_overlay(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    force_overlay: Option<Res<ForceOverlay>>,
+) {
+    if keyboard_input.just_pressed(FORCE_OVERLAY_KEY) {
+        toggle_resource(&mut commands, force_overlay.is_some(), ForceOverlay);
+    }
+}
+
+// Rapier only keeps the computed mass up to date for the bodies that ask for it
+fn read_player_mass(
+    mut commands: Commands,
+    player_query: Query<Entity, (With<Player>, Without<ReadMassProperties>)>,
+) {
+    for player in player_query.iter() {
+        commands
+            .entity(player)
+            .insert(ReadMassProperties::default());
+    }
+}
+
+fn draw_forces(mut gizmos: Gizmos, force_query: Query<(&Transform, &ExternalForce)>) {
+    for (transform, external_force) in force_query.iter() {
+        let position = transform.translation.truncate();
+
+        gizmos.arrow_2d(
+            position,
+            position + external_force.force * FORCE_GIZMO_SCALE,
+            VetovoimaColor::REDDISH,
+        );
+    }
+}
+
+fn draw_player_velocity(mut gizmos: Gizmos, player_query: Query<(&Transform, &Velocity, &Player)>) {
+    for (transform, velocity, player) in player_query.iter() {
+        let position = transform.translation.truncate();
+
+        gizmos.arrow_2d(
+            position,
+            position + velocity.linvel * VELOCITY_GIZMO_SECONDS,
+            player.color(),
+        );
+    }
+}
+
+// The same force as in `apply_forces`, integrated step by step
+fn draw_player_trajectory(
+    mut gizmos: Gizmos,
+    player_query: Query<(
+        &Transform,
+        &Velocity,
+        &Attractable,
+        &ReadMassProperties,
+        &Player,
+    )>,
+    gravity_source: Res<GravitySource>,
+    tuning: Res<Tuning>,
+) {
+    let delta_secs = TRAJECTORY_SECONDS / TRAJECTORY_STEPS as f32;
+
+    for (transform, velocity, attractable, mass_properties, player) in player_query.iter() {
+        let mass = mass_properties.get().mass;
+
+        if mass <= 0.0 {
+            continue;
+        }
+
+        let mut position = transform.translation.truncate();
+        let mut linvel = velocity.linvel;
+        let mut points = vec![position];
+
+        for _ in 0..TRAJECTORY_STEPS {
+            let force =
+                gravity_force_at(position, gravity_source.force, tuning.gravity_force_scale)
+                    * attractable.force_ratio;
+
+            linvel += force / mass * delta_secs;
+            position += linvel * delta_secs;
+            points.push(position);
+        }
+
+        gizmos.linestrip_2d(points, player.color().with_alpha(0.5));
+    }
+}
+
+fn toggle_console(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    dev_console: Option<Res<DevConsole>>,
+    mut button_press: ResMut<ButtonPress>,
+    mut player_two_button_press: ResMut<PlayerTwoButtonPress>,
+) {
+    match dev_console {
+        None if keyboard_input.just_pressed(CONSOLE_TOGGLE_KEY) => {
+            // Keys that were held down would otherwise stay pressed
+            *button_press = ButtonPress::default();
+            *player_two_button_press = PlayerTwoButtonPress::default();
+            commands.insert_resource(DevConsole::default());
+        }
+
+        // Escape is reset, so that it doesn't also take the game to the menu
+        Some(_)
+            if keyboard_input.just_pressed(CONSOLE_TOGGLE_KEY)
+                || keyboard_input.just_released(KeyCode::Escape) =>
+        {
+            keyboard_input.reset(KeyCode::Escape);
+            commands.remove_resource::<DevConsole>();
+        }
+
+        _ => (),
+    }
+}
+
+fn console_input(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut dev_console: ResMut<DevConsole>,
+    mut tuning: ResMut<Tuning>,
+    mut commands: Commands,
+    app_state: Res<State<AppState>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    diagnostics: Res<DiagnosticsStore>,
+) {
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Character(character) if character != CONSOLE_TOGGLE_CHARACTER => {
+                dev_console.input.push_str(character);
+            }
+            Key::Space => dev_console.input.push(' '),
+            Key::Backspace => {
+                dev_console.input.pop();
+            }
+            Key::Enter => {
+                let command = std::mem::take(&mut dev_console.input);
+
+                if command.trim().is_empty() {
+                    continue;
+                }
+
+                let mut next_tuning = tuning.clone();
+                let output = run_tuning_command(&mut next_tuning, &command).unwrap_or_else(|| {
+                    run_game_command(
+                        &command,
+                        &mut commands,
+                        app_state.get(),
+                        &mut next_app_state,
+                        &diagnostics,
+                    )
+                });
+
+                // Only actual changes are applied to the level
+                if next_tuning != *tuning {
+                    *tuning = next_tuning;
+                }
+
+                dev_console.lines.push(format!("> {}", command));
+                dev_console.lines.extend(output);
+            }
+
+            _ => (),
         }
     }
 }
+
+fn run_game_command(
+    command: &str,
+    commands: &mut Commands,
+    app_state: &AppState,
+    next_app_state: &mut NextState<AppState>,
+    diagnostics: &DiagnosticsStore,
+) -> Vec<String> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["level", level_n] => match level_n.parse::<u32>() {
+            Ok(level_n) if level_n >= 1 && *app_state == AppState::InGame => {
+                commands.insert_resource(LevelJump(level_n));
+                next_app_state.set(AppState::LoadingLevel);
+                vec![format!("Jumping to level {}", level_n)]
+            }
+            Ok(level_n) if level_n >= 1 => vec!["Start a game first".to_string()],
+            _ => vec![format!("Invalid level {}", level_n)],
+        },
+
+        ["profile"] => write_profile_report(diagnostics, PROFILE_REPORT_FILENAME),
+        ["profile", filename] => write_profile_report(diagnostics, filename),
+
+        _ => TUNING_COMMANDS_HELP
+            .iter()
+            .chain(CHEATS_HELP.iter())
+            .map(|line| line.to_string())
+            .collect(),
+    }
+}
+
+fn show_console(mut commands: Commands, asset_server: Res<AssetServer>, ui_config: Res<UiConfig>) {
+    let font = asset_server.load(ui_config.font_filename);
+    let text_font = TextFont {
+        font,
+        font_size: ui_config.font_size_body_small,
+        ..Default::default()
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                width: Val::Percent(60.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(VetovoimaColor::BLACKISH.with_alpha(CONSOLE_ALPHA)),
+            GlobalZIndex(3),
+            DevConsoleUi,
+        ))
+        .with_children(|console| {
+            console.spawn((
+                Text::new(""),
+                text_font.clone(),
+                TextColor(VetovoimaColor::WHITEISH),
+                DevConsoleLinesText,
+            ));
+            console.spawn((
+                Text::new("> "),
+                text_font.clone(),
+                TextColor(VetovoimaColor::YELLOWISH),
+                DevConsoleInputText,
+            ));
+        });
+}
+
+fn console_text_update(
+    dev_console: Res<DevConsole>,
+    mut lines_text_query: Query<&mut Text, With<DevConsoleLinesText>>,
+    mut input_text_query: Query<
+        &mut Text,
+        (With<DevConsoleInputText>, Without<DevConsoleLinesText>),
+    >,
+) {
+    let first_visible_line = dev_console
+        .lines
+        .len()
+        .saturating_sub(CONSOLE_VISIBLE_LINES);
+
+    for mut text in lines_text_query.iter_mut() {
+        **text = dev_console.lines[first_visible_line..].join("\n");
+    }
+
+    for mut text in input_text_query.iter_mut() {
+        **text = format!("> {}_", dev_console.input);
+    }
+}
+
+fn hide_console(mut commands: Commands, console_query: Query<Entity, With<DevConsoleUi>>) {
+    for console in console_query.iter() {
+        commands.entity(console).despawn_recursive();
+    }
+}
@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use std::f32::consts::PI;
+
+use crate::{
+    app::{AppState, ButtonPress, GameMode},
+    bot::BotObservation,
+    game::{GameEvent, Player},
+    headless::HeadlessGame,
+    simulation::Attractable,
+};
+
+// The game runs a few ticks per step, agents don't need to decide on every frame
+pub const DEFAULT_TICKS_PER_STEP: u32 = 4;
+const GOAL_REWARD: f32 = 10.0;
+const GAME_OVER_REWARD: f32 = -10.0;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Action {
+    pub forward: bool,
+    pub brake: bool,
+    pub gravity_up: bool,
+    pub gravity_down: bool,
+}
+
+impl From<Action> for ButtonPress {
+    fn from(action: Action) -> Self {
+        ButtonPress {
+            right_pressed: action.forward,
+            left_pressed: action.brake,
+            up_pressed: action.gravity_up,
+            down_pressed: action.gravity_down,
+            ..default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Observation {
+    pub player_position: Vec2,
+    // Radians, zero means that the player is facing along positive X
+    pub player_angle: f32,
+    pub player_velocity: Vec2,
+    pub player_angular_velocity: f32,
+    pub gravity_force: f32,
+    // How far the flag is along the circle in the direction the player is facing (radians, 0..TAU)
+    pub flag_angle_ahead: f32,
+    pub debris_positions: Vec<Vec2>,
+    pub secs_remaining: f32,
+}
+
+// A reinforcement learning style interface to the (headless) game. Every episode is a
+// single level of `GameMode::Seeded`.
+pub struct GameEnvironment {
+    game: HeadlessGame,
+    observation: Observation,
+    ticks_per_step: u32,
+}
+
+impl GameEnvironment {
+    pub fn new(seed: u64) -> Self {
+        GameEnvironment::with_ticks_per_step(seed, DEFAULT_TICKS_PER_STEP)
+    }
+
+    pub fn with_ticks_per_step(seed: u64, ticks_per_step: u32) -> Self {
+        let mut game = HeadlessGame::new(GameMode::Seeded(seed));
+
+        game.start_level();
+        game.clear_events();
+
+        let observation = observe(&mut game);
+
+        GameEnvironment {
+            game,
+            observation,
+            ticks_per_step: ticks_per_step.max(1),
+        }
+    }
+
+    // Starts a new episode
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        *self = GameEnvironment::with_ticks_per_step(seed, self.ticks_per_step);
+
+        self.observation.clone()
+    }
+
+    pub fn step(&mut self, action: Action) -> (Observation, f32, bool) {
+        self.game.press(action.into());
+        self.game.tick(self.ticks_per_step);
+
+        let next_observation = observe(&mut self.game);
+        let goal_reached = self
+            .game
+            .events()
+            .iter()
+            .any(|event| matches!(event, GameEvent::GoalReached(_)));
+        let game_over = self.game.state() == AppState::GameOver;
+
+        let reward = if goal_reached {
+            GOAL_REWARD
+        } else if game_over {
+            GAME_OVER_REWARD
+        } else {
+            progress_reward(&self.observation, &next_observation)
+        };
+        let done = goal_reached || self.game.state() != AppState::InGame;
+
+        self.game.clear_events();
+        self.observation = next_observation;
+
+        (self.observation.clone(), reward, done)
+    }
+
+    pub fn observation(&self) -> &Observation {
+        &self.observation
+    }
+
+    pub fn game(&self) -> &HeadlessGame {
+        &self.game
+    }
+
+    pub fn game_mut(&mut self) -> &mut HeadlessGame {
+        &mut self.game
+    }
+}
+
+// Moving closer to the flag is rewarded, moving away is penalized
+fn progress_reward(previous: &Observation, next: &Observation) -> f32 {
+    let progress = previous.flag_angle_ahead - next.flag_angle_ahead;
+
+    // The angle wraps around when the player turns or passes the flag
+    if progress.abs() > PI {
+        0.0
+    } else {
+        progress
+    }
+}
+
+fn observe(game: &mut HeadlessGame) -> Observation {
+    let secs_remaining = game
+        .level()
+        .map(|level| level.countdown_to_game_over.remaining_secs())
+        .unwrap_or(0.0);
+    let world = game.app_mut().world_mut();
+    let Some(bot_observation) = BotObservation::observe(world, Player::One) else {
+        return Observation::default();
+    };
+    let player_angular_velocity = world
+        .query::<(&Velocity, &Player)>()
+        .iter(world)
+        .find(|(_, player)| **player == Player::One)
+        .map(|(velocity, _)| velocity.angvel)
+        .unwrap_or(0.0);
+    let debris_positions = world
+        .query_filtered::<&Transform, (With<Attractable>, Without<Player>)>()
+        .iter(world)
+        .map(|transform| transform.translation.truncate())
+        .collect();
+
+    Observation {
+        player_position: bot_observation.player_position,
+        player_angle: bot_observation.player_forward.to_angle(),
+        player_velocity: bot_observation.player_velocity,
+        player_angular_velocity,
+        gravity_force: bot_observation.gravity_force,
+        flag_angle_ahead: bot_observation.flag_angle_ahead,
+        debris_positions,
+        secs_remaining,
+    }
+}
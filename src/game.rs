@@ -1,53 +1,84 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+};
 use bevy_prototype_lyon::prelude::*;
-use bevy_rapier2d::prelude::*;
+use bevy_rapier2d::{prelude::*, rapier::geometry::CollisionEventFlags};
 use rand::{prelude::*, seq::IteratorRandom, Rng};
 use rand_distr::{Distribution, Normal, Standard};
 use std::f32::consts::PI;
 use std::time::Duration;
 
 use crate::app::{
-    cursor_visible, AppState, ButtonPress, UiConfig, VetovoimaColor, PIXELS_PER_METER,
+    cursor_visible, AppState, ButtonPress, GameMode, PlayerTwoButtonPress, UiConfig,
+    VetovoimaColor, PIXELS_PER_METER,
+};
+use crate::attract_mode::AttractMode;
+use crate::config::VVConfig;
+use crate::particles::{
+    ParticleBurst, ParticleEffect, ParticleEmitter, ParticleShape, ParticlesPlugin,
 };
+use crate::pool::PoolCommands;
+use crate::ring::{ring_scale, Ring, RingPlugin};
 use crate::simulation::{
     apply_forces, update_gravity, Attractable, GravitySource, GRAVITY_SOURCE_RADIUS_METERS,
 };
+use crate::tuning::Tuning;
 
 const LEVEL_BOUNDS_RADIUS_METERS: f32 = 28.0;
-const PLAYER_WIDTH_METERS: f32 = 0.8;
-const PLAYER_HEIGHT_METERS: f32 = 1.8;
+pub const PLAYER_WIDTH_METERS: f32 = 0.8;
+pub const PLAYER_HEIGHT_METERS: f32 = 1.8;
 const FLAG_WIDTH_METERS: f32 = 0.55;
 const FLAG_HEIGHT_METERS: f32 = LEVEL_BOUNDS_RADIUS_METERS / 4.8;
 
 const Z_INDEX_WORLD: f32 = 1.0;
-const Z_INDEX_OBJECTS: f32 = 2.0;
+pub const Z_INDEX_OBJECTS: f32 = 2.0;
 
-const PLAYER_MAX_FORWARD_VELOCITY: f32 = 100.0;
-const PLAYER_SLOW_DOWN_VELOCITY: f32 = -0.5 * PLAYER_MAX_FORWARD_VELOCITY;
-const PLAYER_MAX_ANGULAR_VELOCITY: f32 = 50.0;
+// Relative to the max forward velocity (see `Tuning`)
+const PLAYER_SLOW_DOWN_VELOCITY_RATIO: f32 = -0.5;
+// The player only brakes (and kicks up dust) when moving forward faster than this
+const PLAYER_BRAKE_MIN_VELOCITY: f32 = 15.0;
+// How many terrain vertices (2 degrees each) apart the players start from in versus mode
+const PLAYER_SPAWN_SPACING_STEPS: usize = 4;
 
-const ACCEL_PARTICLE_SPAWN_INTERVAL: f32 = 0.04;
-const ACCEL_PARTICLE_LIFETIME: f32 = 0.4;
-const ACCEL_PARTICLE_BASE_SPEED: f32 = 40.0;
-const ACCEL_PARTICLE_SPREAD: f32 = 35.0;
 const ACCEL_PARTICLE_RADIUS_MIN: f32 = 4.0;
 const ACCEL_PARTICLE_RADIUS_MAX: f32 = 8.5;
-const Z_INDEX_PARTICLES: f32 = 1.5;
-
-const BASE_OBJECTS_AMOUNT: u32 = 16;
-const MAX_OBJECTS_AMOUNT: u32 = 60;
+const BRAKE_DUST_SPAWN_INTERVAL_SECONDS: f32 = 0.06;
+// Slower bumps between the debris (pixels/s) don't chip off any particles
+const DEBRIS_IMPACT_MIN_VELOCITY: f32 = 60.0;
+// The debris shapes are made once and scaled to the size of each object. There are a few
+// n-gons of each amount of sides, always the same ones.
+const DEBRIS_BASE_RADIUS_METERS: f32 = 0.5;
+const DEBRIS_NGON_MIN_SIDES: u32 = 5;
+const DEBRIS_NGON_MAX_SIDES: u32 = 10;
+const DEBRIS_NGON_VARIANTS: usize = 4;
+// Relative to the radius
+const DEBRIS_NGON_VERTEX_DEVIATION: f32 = 0.08;
+const DEBRIS_SHAPES_SEED: u64 = 0xDEB2_0001;
 
 const LOADING_TIMER_DURATION_SECONDS: f32 = 3.0;
+// The level (and the goal celebration) is still visible while the loading screen fades in
+const LOADING_SCREEN_FADE_IN_SECONDS: f32 = 0.6;
 
+// The debris has a group of its own, so that the players can be made to go through it
 const DEFAULT_COLLISION_GROUP: CollisionGroups =
-    CollisionGroups::new(Group::GROUP_1, Group::GROUP_1);
+    CollisionGroups::new(Group::GROUP_1, Group::GROUP_1.union(Group::GROUP_3));
 const SECONDARY_COLLISION_GROUP: CollisionGroups =
     CollisionGroups::new(Group::GROUP_2, Group::GROUP_2);
+const DEBRIS_COLLISION_GROUP: CollisionGroups = CollisionGroups::new(
+    Group::GROUP_2.union(Group::GROUP_3),
+    Group::GROUP_1.union(Group::GROUP_2).union(Group::GROUP_3),
+);
+const NO_DEBRIS_COLLISION_GROUP: CollisionGroups =
+    CollisionGroups::new(Group::GROUP_1, Group::GROUP_1);
 
-#[derive(Event)]
+#[derive(Event, Clone, Debug, PartialEq)]
 pub enum GameEvent {
     CountdownTick(u32),
-    GoalReached,
+    GoalReached(Player),
     GameOver,
     LevelStarted,
     PlayerCollided(Duration, f32),
@@ -55,8 +86,9 @@ pub enum GameEvent {
 
 #[derive(Component, Clone, Debug, Resource)]
 pub struct GameLevel {
-    n: u32,
-    countdown_to_game_over: Timer,
+    pub n: u32,
+    pub seed: u64,
+    pub countdown_to_game_over: Timer,
     terrain_vertices: Vec<Vec2>,
     elevation_vertices: Vec<Vec2>,
 }
@@ -91,13 +123,62 @@ impl Distribution<ObjectKind> for Standard {
     }
 }
 
-#[derive(PartialEq)]
+#[derive(Component, PartialEq, Clone, Copy)]
 enum ObjectDensity {
     Light,
     Medium,
     Heavy,
 }
 
+impl ObjectDensity {
+    fn value(&self, tuning: &Tuning) -> f32 {
+        match self {
+            ObjectDensity::Light => tuning.light_object_density,
+            ObjectDensity::Medium => tuning.medium_object_density,
+            ObjectDensity::Heavy => tuning.heavy_object_density,
+        }
+    }
+
+    fn color(&self, colorblind_palette: bool) -> Color {
+        match (self, colorblind_palette) {
+            (ObjectDensity::Light, false) => VetovoimaColor::GREENISH,
+            (ObjectDensity::Light, true) => VetovoimaColor::CYANISH,
+            (ObjectDensity::Medium, false) => VetovoimaColor::REDDISH,
+            (ObjectDensity::Medium, true) => VetovoimaColor::ORANGEISH,
+            (ObjectDensity::Heavy, _) => VetovoimaColor::WHITEISH,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct DebrisShape {
+    // Both have a radius of about 1.0
+    mesh: Handle<Mesh>,
+    collider: Collider,
+}
+
+// Every piece of debris uses one of these, so pooled debris doesn't need new geometry when
+// it's used again. The materials follow the palette.
+#[derive(Resource)]
+struct DebrisShapes {
+    circle: DebrisShape,
+    // By the amount of sides
+    ngons: Vec<Vec<DebrisShape>>,
+    light_material: Handle<ColorMaterial>,
+    medium_material: Handle<ColorMaterial>,
+    heavy_material: Handle<ColorMaterial>,
+}
+
+impl DebrisShapes {
+    fn material(&self, density: ObjectDensity) -> Handle<ColorMaterial> {
+        match density {
+            ObjectDensity::Light => self.light_material.clone(),
+            ObjectDensity::Medium => self.medium_material.clone(),
+            ObjectDensity::Heavy => self.heavy_material.clone(),
+        }
+    }
+}
+
 impl Distribution<ObjectDensity> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ObjectDensity {
         let distance: f32 = rng.gen();
@@ -125,26 +206,95 @@ struct LoadingLevelText;
 struct GameObject;
 
 #[derive(Component)]
-struct GravityRing(f32);
+pub struct GravityRing;
 
 #[derive(Component)]
-struct AccelParticle {
-    lifetime: Timer,
-    velocity: Vec2,
-    hue: f32,
+struct ExhaustEmitter;
+
+#[derive(Component)]
+struct BrakeDustEmitter;
+
+// What the debris loses when it goes to the pool at the end of the level
+type DebrisPhysics = (
+    GameObject,
+    Attractable,
+    RigidBody,
+    Collider,
+    ColliderMassProperties,
+    Restitution,
+    GravityScale,
+    ExternalForce,
+    Velocity,
+    ActiveEvents,
+    CollisionGroups,
+);
+
+// When present, this level is loaded next instead of the one following the current level
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct LevelJump(pub u32);
+
+// When present, the next level is generated from this seed instead of the game mode's seed
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct NextLevelSeed(pub u64);
+
+// When present, the countdown doesn't run
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct CountdownFrozen;
+
+// When present, the players go through the debris
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct DebrisCollisionsOff;
+
+#[derive(Component, Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Player {
+    One,
+    Two,
 }
 
-#[derive(Resource)]
-struct ParticleSpawnTimer(Timer);
+impl Player {
+    pub fn color(&self) -> Color {
+        match self {
+            Player::One => VetovoimaColor::YELLOWISH,
+            Player::Two => VetovoimaColor::PINKISH,
+        }
+    }
 
-#[derive(Component)]
-pub struct Player;
+    // Each player has their own set of keys
+    fn controls<'a>(
+        &self,
+        button_press: &'a ButtonPress,
+        player_two_button_press: &'a PlayerTwoButtonPress,
+    ) -> &'a ButtonPress {
+        match self {
+            Player::One => button_press,
+            Player::Two => &player_two_button_press.0,
+        }
+    }
+}
+
+// A PD controller that keeps the player upright relative to the gravity source.
+// Stiffness is the angular acceleration per radian of orientation error, damping
+// the (negative) angular acceleration per unit of current angular velocity.
+// The angle error is signed (radians), positive means counterclockwise.
+pub fn next_upright_angular_velocity(
+    tuning: &Tuning,
+    angle_error: f32,
+    angular_velocity: f32,
+    delta_secs: f32,
+) -> f32 {
+    let angular_acceleration = tuning.player_upright_stiffness * angle_error
+        - tuning.player_upright_damping * angular_velocity;
+    let next_angular_velocity = angular_velocity + angular_acceleration * delta_secs;
+    let max_angular_velocity = tuning.player_max_angular_velocity;
+
+    next_angular_velocity.clamp(-max_angular_velocity, max_angular_velocity)
+}
 
 #[derive(Component)]
-struct Flag;
+pub struct Flag;
 
 #[derive(Component)]
-struct FlagAura(f32);
+pub struct FlagAura;
 
 #[derive(Component)]
 struct GameUI;
@@ -156,16 +306,18 @@ pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(ShapePlugin)
+        app.add_plugins((ShapePlugin, RingPlugin, ParticlesPlugin))
             .add_event::<GameEvent>()
             .insert_resource(PlayerCollision::default())
-            .insert_resource(ParticleSpawnTimer(Timer::from_seconds(
-                ACCEL_PARTICLE_SPAWN_INTERVAL,
-                TimerMode::Repeating,
-            )))
+            .add_systems(Startup, debris_shapes_setup)
             .add_systems(
                 OnEnter(AppState::LoadingLevel),
-                (cursor_visible::<false>, loading_screen_setup, game_setup),
+                (
+                    // The menu stays usable during the demo
+                    cursor_visible::<false>.run_if(not(resource_exists::<AttractMode>)),
+                    loading_screen_setup,
+                    game_setup,
+                ),
             )
             .add_systems(
                 Update,
@@ -176,25 +328,38 @@ impl Plugin for GamePlugin {
             .add_systems(
                 Update,
                 (
-                    update_gravity,
-                    apply_forces,
-                    update_player_velocity,
+                    // In a fixed order, so that the same input always plays out the same way
+                    (update_gravity, apply_forces, update_player_velocity).chain(),
                     update_flag_aura,
                     check_goal_reached,
+                    celebrate_goal.after(check_goal_reached),
                     detect_player_collision,
-                    update_game_over_countdown,
+                    detect_debris_impacts,
+                    update_game_over_countdown.run_if(not(resource_exists::<CountdownFrozen>)),
+                    update_player_collision_groups,
                     countdown_text_update,
                     update_gravity_visuals,
-                    spawn_accel_particles,
-                    update_accel_particles,
+                    update_player_emitters,
                 )
                     .run_if(in_state(AppState::InGame)),
             )
-            .add_systems(OnExit(AppState::InGame), (game_cleanup, game_ui_cleanup));
+            .add_systems(
+                Update,
+                apply_object_palette.run_if(resource_changed::<VVConfig>),
+            )
+            .add_systems(Update, apply_tuning.run_if(resource_changed::<Tuning>))
+            .add_systems(OnExit(AppState::InGame), (game_cleanup, game_ui_cleanup))
+            // The level may also be left while it's still loading
+            .add_systems(OnEnter(AppState::InMenu), game_cleanup);
     }
 }
 
-fn create_game_level(current_level_value: u32) -> GameLevel {
+fn create_game_level(
+    current_level_value: u32,
+    seed: u64,
+    tuning: &Tuning,
+    rng: &mut impl Rng,
+) -> GameLevel {
     let next_level_n = current_level_value + 1;
     let radius_pixels = LEVEL_BOUNDS_RADIUS_METERS * PIXELS_PER_METER;
     // the outer edge (rim) of the circle polygon
@@ -219,7 +384,7 @@ fn create_game_level(current_level_value: u32) -> GameLevel {
             let variation = if step > 0 && step < inner_circle_steps {
                 let std_deviation = 0.086 * PIXELS_PER_METER;
                 let normal_distribution = Normal::new(mean, std_deviation).unwrap();
-                normal_distribution.sample(&mut rand::thread_rng())
+                normal_distribution.sample(&mut *rng)
             } else {
                 mean
             };
@@ -234,49 +399,165 @@ fn create_game_level(current_level_value: u32) -> GameLevel {
         })
         .collect();
 
-    let countdown: u64 = match next_level_n {
-        1..=4 => 60,
-        5..=9 => 40,
-        10..=25 => 30,
-        _ => 20,
-    };
+    let countdown = tuning.level_countdown_secs(next_level_n);
 
     GameLevel {
         n: next_level_n,
+        seed,
         countdown_to_game_over: Timer::new(Duration::from_secs(countdown), TimerMode::Once),
         terrain_vertices: vec![elevation_vertices.clone(), rim_vertices].concat(),
         elevation_vertices,
     }
 }
 
-fn game_cleanup(mut commands: Commands, game_object_query: Query<Entity, With<GameObject>>) {
+// The countdown the game ships with (the tuning may change it while developing)
+pub fn level_countdown_secs(level_n: u32) -> u64 {
+    Tuning::default().level_countdown_secs(level_n)
+}
+
+fn debris_shapes_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    vv_config: Res<VVConfig>,
+) {
+    let mut rng = StdRng::seed_from_u64(DEBRIS_SHAPES_SEED);
+    let ngons = (DEBRIS_NGON_MIN_SIDES..=DEBRIS_NGON_MAX_SIDES)
+        .map(|sides_amount| {
+            (0..DEBRIS_NGON_VARIANTS)
+                .map(|_| ngon_shape(sides_amount, &mut meshes, &mut rng))
+                .collect()
+        })
+        .collect();
+    let palette = vv_config.colorblind_palette;
+
+    commands.insert_resource(DebrisShapes {
+        circle: DebrisShape {
+            mesh: meshes.add(Circle::new(1.0).mesh().resolution(64)),
+            collider: Collider::ball(1.0),
+        },
+        ngons,
+        light_material: materials.add(ObjectDensity::Light.color(palette)),
+        medium_material: materials.add(ObjectDensity::Medium.color(palette)),
+        heavy_material: materials.add(ObjectDensity::Heavy.color(palette)),
+    });
+}
+
+fn apply_object_palette(
+    debris_shapes: Option<Res<DebrisShapes>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    vv_config: Res<VVConfig>,
+) {
+    let Some(debris_shapes) = debris_shapes else {
+        return;
+    };
+
+    for density in [
+        ObjectDensity::Light,
+        ObjectDensity::Medium,
+        ObjectDensity::Heavy,
+    ] {
+        if let Some(material) = materials.get_mut(&debris_shapes.material(density)) {
+            material.color = density.color(vv_config.colorblind_palette);
+        }
+    }
+}
+
+// Objects that are already in the level follow the changes too
+fn apply_tuning(
+    mut object_query: Query<(&mut ColliderMassProperties, &ObjectDensity)>,
+    mut player_query: Query<
+        (
+            &mut ContactForceEventThreshold,
+            &mut AdditionalMassProperties,
+        ),
+        With<Player>,
+    >,
+    mut exhaust_query: Query<&mut ParticleEmitter, With<ExhaustEmitter>>,
+    tuning: Res<Tuning>,
+) {
+    for (mut mass_properties, density) in object_query.iter_mut() {
+        *mass_properties = ColliderMassProperties::Density(density.value(&tuning));
+    }
+
+    for (mut threshold, mut mass_properties) in player_query.iter_mut() {
+        threshold.0 = tuning.player_contact_force_threshold;
+        *mass_properties = player_mass_properties(tuning.player_mass);
+    }
+
+    for mut emitter in exhaust_query.iter_mut() {
+        emitter.effect = exhaust_effect(&tuning);
+        emitter.interval.set_duration(Duration::from_secs_f32(
+            tuning.accel_particle_spawn_interval,
+        ));
+    }
+}
+
+// The debris is kept for the next level (and the particles go to the pool on their own)
+fn game_cleanup(
+    mut commands: Commands,
+    game_object_query: Query<Entity, (With<GameObject>, Without<ObjectDensity>)>,
+    debris_query: Query<Entity, (With<GameObject>, With<ObjectDensity>)>,
+) {
     for object in game_object_query.iter() {
-        commands.entity(object).despawn();
+        commands.entity(object).despawn_recursive();
+    }
+
+    for debris in debris_query.iter() {
+        commands.release::<ObjectDensity, DebrisPhysics>(debris);
     }
 }
 
 fn game_setup(
     mut commands: Commands,
     game_level: Option<Res<GameLevel>>,
-    mut gravity_source: ResMut<GravitySource>,
-    mut player_collision: ResMut<PlayerCollision>,
+    level_jump: Option<Res<LevelJump>>,
+    next_level_seed: Option<Res<NextLevelSeed>>,
+    game_mode: Res<GameMode>,
+    debris_shapes: Res<DebrisShapes>,
+    tuning: Res<Tuning>,
 ) {
-    let current_game_level_n = match game_level {
-        Some(level) => level.n,
-        None => 0,
+    let current_game_level_n = match (level_jump, game_level) {
+        (Some(jump), _) => {
+            commands.remove_resource::<LevelJump>();
+            jump.0.saturating_sub(1)
+        }
+        (None, Some(level)) => level.n,
+        (None, None) => 0,
+    };
+    // Everything random about the level is derived from the seed
+    let seed = match next_level_seed {
+        Some(next_level_seed) => {
+            commands.remove_resource::<NextLevelSeed>();
+            next_level_seed.0
+        }
+        None => game_mode.level_seed(current_game_level_n + 1),
     };
-    let next_game_level = create_game_level(current_game_level_n);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let next_game_level = create_game_level(current_game_level_n, seed, &tuning, &mut rng);
 
     // Reset some resources
-    *gravity_source = GravitySource::default();
-    *player_collision = PlayerCollision::default();
+    commands.insert_resource(GravitySource::initial(&tuning));
+    commands.insert_resource(PlayerCollision::default());
 
     // Will replace the current game level with the next
     commands.insert_resource(next_game_level.clone());
 
     spawn_level(&mut commands, &next_game_level);
-    spawn_objects(&mut commands, next_game_level.n);
-    spawn_player_and_and_goal(&mut commands, &next_game_level);
+    spawn_objects(
+        &mut commands,
+        next_game_level.n,
+        &debris_shapes,
+        &tuning,
+        &mut rng,
+    );
+    spawn_player_and_and_goal(
+        &mut commands,
+        &next_game_level,
+        game_mode.players(),
+        &tuning,
+        &mut rng,
+    );
 }
 
 fn loading_screen_setup(
@@ -299,7 +580,7 @@ fn loading_screen_setup(
                 align_items: AlignItems::Center,
                 ..default()
             },
-            BackgroundColor(VetovoimaColor::BLACKISH),
+            BackgroundColor(VetovoimaColor::BLACKISH.with_alpha(0.0)),
             LoadingScreen,
         ))
         .with_children(|container| {
@@ -380,44 +661,52 @@ fn spawn_level(commands: &mut Commands, game_level: &GameLevel) {
     for n in 1..=gravity_rings_amount {
         let n_f = n as f32;
         let radius = ring_frequency * n_f;
-        let shape = shapes::Circle {
-            radius,
-            center: Vec2::ZERO,
-        };
 
         commands.spawn((
-            ShapeBundle {
-                path: GeometryBuilder::build_as(&shape),
-                transform: Transform::from_translation(Vec3::new(0.0, 0.0, Z_INDEX_WORLD)),
-                ..Default::default()
+            Ring {
+                color: Color::hsla(0.0, 1.0, 1.0, 0.0),
             },
-            Stroke::new(Color::hsla(0.0, 1.0, 1.0, 0.0), 1.0),
-            GravityRing(radius),
+            Transform::from_translation(Vec3::new(0.0, 0.0, Z_INDEX_WORLD))
+                .with_scale(ring_scale(radius)),
+            GravityRing,
             GameObject,
         ));
     }
 }
 
-fn spawn_objects(commands: &mut Commands, game_level_n: u32) {
-    let difficulty_bonus = 2 * game_level_n;
-    let objects_amount = (BASE_OBJECTS_AMOUNT + difficulty_bonus).min(MAX_OBJECTS_AMOUNT);
+fn spawn_objects(
+    commands: &mut Commands,
+    game_level_n: u32,
+    debris_shapes: &DebrisShapes,
+    tuning: &Tuning,
+    rng: &mut impl Rng,
+) {
+    let objects_amount = tuning.objects_amount(game_level_n);
     let full_turn_radians = 2.0 * PI;
 
     for n in 1..=objects_amount {
-        let object_density: ObjectDensity = rand::random();
+        let object_density: ObjectDensity = rng.gen();
         let (object_kind, distance_range) = match object_density {
             ObjectDensity::Light => (ObjectKind::Circle, 0.15..=0.85),
-            ObjectDensity::Medium => (rand::random(), 0.25..=0.75),
+            ObjectDensity::Medium => (rng.gen(), 0.25..=0.75),
             ObjectDensity::Heavy => (ObjectKind::Ngon, 0.5..=0.85),
         };
         let distance_from_center_meters: f32 =
-            thread_rng().gen_range(distance_range) * LEVEL_BOUNDS_RADIUS_METERS;
+            rng.gen_range(distance_range) * LEVEL_BOUNDS_RADIUS_METERS;
         let base_x = distance_from_center_meters * PIXELS_PER_METER;
         let angle_radians = (full_turn_radians / objects_amount as f32) * n as f32;
         let mut transform = Transform::from_translation(Vec3::new(base_x, 0.0, Z_INDEX_OBJECTS));
 
         transform.rotate_around(Vec3::ZERO, Quat::from_rotation_z(angle_radians));
-        spawn_object(commands, object_kind, object_density, transform);
+        spawn_object(
+            commands,
+            object_kind,
+            object_density,
+            debris_shapes,
+            tuning,
+            transform,
+            rng,
+        );
     }
 }
 
@@ -425,61 +714,61 @@ fn spawn_object(
     commands: &mut Commands,
     kind: ObjectKind,
     density: ObjectDensity,
-    transform: Transform,
+    debris_shapes: &DebrisShapes,
+    tuning: &Tuning,
+    mut transform: Transform,
+    rng: &mut impl Rng,
 ) {
-    let (density_value, base_scale_factor, color, max_random_force, max_random_torque) =
-        match density {
-            ObjectDensity::Light => (0.75, 1.0, VetovoimaColor::GREENISH, 3.0, 0.3),
-            ObjectDensity::Medium => (1.0, 2.0, VetovoimaColor::REDDISH, 30.0, 1.0),
-            ObjectDensity::Heavy => (10.0, 3.2, VetovoimaColor::WHITEISH, 200.0, 20.0),
-        };
-    let scale_variation: f32 = thread_rng().gen_range(-0.2..0.4);
+    let (base_scale_factor, max_random_force, max_random_torque) = match density {
+        ObjectDensity::Light => (1.0, 3.0, 0.3),
+        ObjectDensity::Medium => (2.0, 30.0, 1.0),
+        ObjectDensity::Heavy => (3.2, 200.0, 20.0),
+    };
+    let scale_variation: f32 = rng.gen_range(-0.2..0.4);
     let scale_factor = (base_scale_factor + (base_scale_factor * scale_variation)).max(1.0);
-    let (path, collider, restitution_coefficient) = match kind {
-        ObjectKind::Ngon => ngon_props(scale_factor),
-        ObjectKind::Circle => circle_props(scale_factor),
+    let radius = DEBRIS_BASE_RADIUS_METERS * scale_factor * PIXELS_PER_METER;
+    let (shape, restitution_coefficient) = match kind {
+        ObjectKind::Ngon => {
+            let sides_amount = rng.gen_range(DEBRIS_NGON_MIN_SIDES..=DEBRIS_NGON_MAX_SIDES);
+            let variants = &debris_shapes.ngons[(sides_amount - DEBRIS_NGON_MIN_SIDES) as usize];
+
+            (&variants[rng.gen_range(0..variants.len())], 0.1)
+        }
+        ObjectKind::Circle => (&debris_shapes.circle, 1.0),
     };
 
-    commands.spawn((
-        ShapeBundle {
-            path,
+    // The collider is scaled along with the transform
+    transform.scale = Vec3::new(radius, radius, 1.0);
+
+    commands.spawn_pooled::<ObjectDensity>((
+        (
             transform,
-            ..Default::default()
-        },
-        Fill {
-            options: FillOptions::default(),
-            color: color,
-        },
+            Visibility::Inherited,
+            Mesh2d(shape.mesh.clone()),
+            MeshMaterial2d(debris_shapes.material(density)),
+        ),
         GameObject,
+        density,
         Attractable { force_ratio: 1.0 },
         RigidBody::Dynamic,
-        collider,
-        ColliderMassProperties::Density(density_value),
+        shape.collider.clone(),
+        ColliderMassProperties::Density(density.value(tuning)),
         Restitution::coefficient(restitution_coefficient),
         GravityScale(0.0),
-        random_external_force(max_random_force, max_random_torque),
-        CollisionGroups::new(
-            DEFAULT_COLLISION_GROUP
-                .memberships
-                .union(SECONDARY_COLLISION_GROUP.memberships),
-            DEFAULT_COLLISION_GROUP
-                .filters
-                .union(SECONDARY_COLLISION_GROUP.filters),
-        ),
+        random_external_force(max_random_force, max_random_torque, rng),
+        Velocity::zero(),
+        ActiveEvents::COLLISION_EVENTS,
+        DEBRIS_COLLISION_GROUP,
     ));
 }
 
-fn ngon_props(scale_factor: f32) -> (Path, Collider, f32) {
-    let base_radius: f32 = 0.5 * scale_factor * PIXELS_PER_METER;
+fn ngon_shape(sides_amount: u32, meshes: &mut Assets<Mesh>, rng: &mut impl Rng) -> DebrisShape {
     let full_turn_radians = 2.0 * PI;
-    let std_deviation = 1.3;
-    let normal_distribution = Normal::new(base_radius, std_deviation).unwrap();
-    let sides_amount: u32 = thread_rng().gen_range(5..=10);
+    let normal_distribution = Normal::new(1.0, DEBRIS_NGON_VERTEX_DEVIATION).unwrap();
     let ngon_vertices: Vec<Vec2> = (1..=sides_amount)
-        .into_iter()
         .map(|side_n| {
             let angle_radians = (full_turn_radians / sides_amount as f32) * side_n as f32;
-            let distance = normal_distribution.sample(&mut rand::thread_rng());
+            let distance = normal_distribution.sample(&mut *rng);
 
             // polar -> cartesian conversion
             Vec2::new(
@@ -488,32 +777,34 @@ fn ngon_props(scale_factor: f32) -> (Path, Collider, f32) {
             )
         })
         .collect();
-
-    let shape = shapes::Polygon {
-        points: ngon_vertices.clone(),
-        closed: true,
-    };
-    let path = GeometryBuilder::build_as(&shape);
-    let collider =
-        Collider::convex_hull(&ngon_vertices).unwrap_or_else(|| Collider::ball(base_radius));
-
-    (path, collider, 0.1)
-}
-
-fn circle_props(scale_factor: f32) -> (Path, Collider, f32) {
-    let radius: f32 = 0.5 * scale_factor * PIXELS_PER_METER;
-
-    let shape = shapes::Circle {
-        radius,
-        center: Vec2::ZERO,
-    };
-    let path = GeometryBuilder::build_as(&shape);
-    let collider = Collider::ball(radius);
-
-    (path, collider, 1.0)
+    // A triangle fan around the center, which is always inside the n-gon
+    let positions: Vec<[f32; 3]> = std::iter::once(Vec2::ZERO)
+        .chain(ngon_vertices.iter().copied())
+        .map(|vertex| [vertex.x, vertex.y, 0.0])
+        .collect();
+    let indices: Vec<u32> = (1..=sides_amount)
+        .flat_map(|n| [0, n, n % sides_amount + 1])
+        .collect();
+    let mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_indices(Indices::U32(indices));
+
+    DebrisShape {
+        mesh: meshes.add(mesh),
+        collider: Collider::convex_hull(&ngon_vertices).unwrap_or_else(|| Collider::ball(1.0)),
+    }
 }
 
-fn spawn_player_and_and_goal(commands: &mut Commands, game_level: &GameLevel) {
+fn spawn_player_and_and_goal(
+    commands: &mut Commands,
+    game_level: &GameLevel,
+    players: &[Player],
+    tuning: &Tuning,
+    rng: &mut impl Rng,
+) {
     // Flag (goal)
     let flag_extent_x = FLAG_WIDTH_METERS * PIXELS_PER_METER;
     let flag_extent_y = FLAG_HEIGHT_METERS * PIXELS_PER_METER;
@@ -521,7 +812,7 @@ fn spawn_player_and_and_goal(commands: &mut Commands, game_level: &GameLevel) {
     let flag_anchor = game_level
         .elevation_vertices
         .iter()
-        .choose(&mut thread_rng())
+        .choose(rng)
         .unwrap_or(&Vec2::ZERO);
     let flag_transform = stand_upright_at_anchor(flag_anchor, flag_extent_y, Z_INDEX_OBJECTS);
 
@@ -547,21 +838,15 @@ fn spawn_player_and_and_goal(commands: &mut Commands, game_level: &GameLevel) {
         DEFAULT_COLLISION_GROUP,
     ));
 
-    // Flag force aura (sweeps gravity objects away from the flag to let the player in)
-    let aura_shape = shapes::Circle {
-        radius: 1.0,
-        center: Vec2::ZERO,
-    };
-
+    // Flag force aura (sweeps gravity objects away from the flag to let the player in).
+    // The collider grows with the scale of the transform.
     commands.spawn((
-        ShapeBundle {
-            path: GeometryBuilder::build_as(&aura_shape),
-            transform: Transform::from_translation(flag_transform.translation),
-            ..Default::default()
+        Ring {
+            color: Color::hsla(0.0, 1.0, 1.0, 0.0),
         },
-        Stroke::new(Color::hsla(0.0, 1.0, 1.0, 0.0), 1.0),
+        Transform::from_translation(flag_transform.translation).with_scale(ring_scale(1.0)),
         GameObject,
-        FlagAura(0.0),
+        FlagAura,
         RigidBody::Fixed,
         Collider::ball(1.0),
         Restitution::coefficient(1.0),
@@ -569,17 +854,37 @@ fn spawn_player_and_and_goal(commands: &mut Commands, game_level: &GameLevel) {
     ));
 
     // "Player"
-    let player_extent_x = PLAYER_WIDTH_METERS * PIXELS_PER_METER;
-    let player_extent_y = PLAYER_HEIGHT_METERS * PIXELS_PER_METER;
     let level_bounds_radius_pixels = LEVEL_BOUNDS_RADIUS_METERS * PIXELS_PER_METER;
     let min_player_distance_from_flag = level_bounds_radius_pixels * 1.8;
-    let fallback_anchor = &Vec2::new(0.0, level_bounds_radius_pixels * -0.5);
-    let player_anchor = game_level
+    let fallback_anchor = Vec2::new(0.0, level_bounds_radius_pixels * -0.5);
+    let player_anchor_index = game_level
         .elevation_vertices
         .iter()
-        .find(|ground_vertex| ground_vertex.distance(*flag_anchor) > min_player_distance_from_flag)
-        .unwrap_or(fallback_anchor);
-    let player_transform = stand_upright_at_anchor(player_anchor, player_extent_y, Z_INDEX_OBJECTS);
+        .position(|ground_vertex| {
+            ground_vertex.distance(*flag_anchor) > min_player_distance_from_flag
+        });
+
+    for (player_index, player) in players.iter().enumerate() {
+        // Other players line up next to the first one
+        let anchor_offset = player_index * PLAYER_SPAWN_SPACING_STEPS;
+        let player_anchor = match player_anchor_index {
+            Some(anchor_index) => {
+                let vertices_amount = game_level.elevation_vertices.len();
+                game_level.elevation_vertices[(anchor_index + anchor_offset) % vertices_amount]
+            }
+            None => {
+                Vec2::from_angle((anchor_offset as f32 * 2.0).to_radians()).rotate(fallback_anchor)
+            }
+        };
+
+        spawn_player(commands, *player, &player_anchor, tuning);
+    }
+}
+
+fn spawn_player(commands: &mut Commands, player: Player, anchor: &Vec2, tuning: &Tuning) {
+    let player_extent_x = PLAYER_WIDTH_METERS * PIXELS_PER_METER;
+    let player_extent_y = PLAYER_HEIGHT_METERS * PIXELS_PER_METER;
+    let player_transform = stand_upright_at_anchor(anchor, player_extent_y, Z_INDEX_OBJECTS);
 
     commands
         .spawn((
@@ -592,11 +897,11 @@ fn spawn_player_and_and_goal(commands: &mut Commands, game_level: &GameLevel) {
                 transform: player_transform,
                 ..Default::default()
             },
-            Player,
+            player,
             GameObject,
             Fill {
                 options: FillOptions::default(),
-                color: VetovoimaColor::YELLOWISH,
+                color: player.color(),
             },
             Attractable { force_ratio: 2.0 },
         ))
@@ -604,12 +909,8 @@ fn spawn_player_and_and_goal(commands: &mut Commands, game_level: &GameLevel) {
             RigidBody::Dynamic,
             Collider::cuboid(player_extent_x / 2.0, player_extent_y / 2.0),
             ActiveEvents::CONTACT_FORCE_EVENTS,
-            ContactForceEventThreshold(225.0),
-            AdditionalMassProperties::MassProperties(MassProperties {
-                local_center_of_mass: Vec2::new(0.0, -player_extent_y),
-                mass: 0.15,
-                principal_inertia: 0.0,
-            }),
+            ContactForceEventThreshold(tuning.player_contact_force_threshold),
+            player_mass_properties(tuning.player_mass),
             Restitution::coefficient(0.1),
             GravityScale(0.0),
             ExternalForce {
@@ -621,7 +922,38 @@ fn spawn_player_and_and_goal(commands: &mut Commands, game_level: &GameLevel) {
                 angvel: 0.0,
             },
             DEFAULT_COLLISION_GROUP,
-        ));
+        ))
+        .with_children(|player_entity| {
+            // Behind the player
+            player_entity.spawn((
+                Transform::from_xyz(-player_extent_x / 2.0, 0.0, 0.0),
+                ParticleEmitter::new(
+                    exhaust_effect(tuning),
+                    Vec2::NEG_X,
+                    tuning.accel_particle_spawn_interval,
+                ),
+                ExhaustEmitter,
+            ));
+            // At the feet, kicked up and forward
+            player_entity.spawn((
+                Transform::from_xyz(player_extent_x / 2.0, -player_extent_y / 2.0, 0.0),
+                ParticleEmitter::new(
+                    brake_dust_effect(),
+                    Vec2::new(1.0, 1.5),
+                    BRAKE_DUST_SPAWN_INTERVAL_SECONDS,
+                ),
+                BrakeDustEmitter,
+            ));
+        });
+}
+
+// The center of mass is below the player, which helps to keep it upright
+fn player_mass_properties(mass: f32) -> AdditionalMassProperties {
+    AdditionalMassProperties::MassProperties(MassProperties {
+        local_center_of_mass: Vec2::new(0.0, -PLAYER_HEIGHT_METERS * PIXELS_PER_METER),
+        mass,
+        principal_inertia: 0.0,
+    })
 }
 
 fn stand_upright_at_anchor(anchor: &Vec2, object_height: f32, z_index: f32) -> Transform {
@@ -682,6 +1014,7 @@ fn game_ui_setup(mut commands: Commands, asset_server: Res<AssetServer>, ui_conf
 fn loading_update(
     mut commands: Commands,
     mut level_text_query: Query<&mut TextSpan, With<LoadingLevelText>>,
+    mut loading_screen_query: Query<&mut BackgroundColor, With<LoadingScreen>>,
     mut loading: ResMut<LoadingState>,
     mut app_state: ResMut<NextState<AppState>>,
     game_level: Option<Res<GameLevel>>,
@@ -689,6 +1022,12 @@ fn loading_update(
 ) {
     loading.0.tick(time.delta());
 
+    for mut background_color in loading_screen_query.iter_mut() {
+        let alpha = loading.0.elapsed_secs() / LOADING_SCREEN_FADE_IN_SECONDS;
+
+        background_color.0.set_alpha(alpha.min(1.0));
+    }
+
     match game_level {
         Some(level) => {
             let mut span = level_text_query
@@ -719,56 +1058,45 @@ fn loading_finished_effects(
 }
 
 fn update_player_velocity(
-    mut velocities: Query<(&mut Velocity, &Transform), With<Player>>,
+    mut velocities: Query<(&mut Velocity, &Transform, &Player)>,
     button_press: Res<ButtonPress>,
+    player_two_button_press: Res<PlayerTwoButtonPress>,
+    tuning: Res<Tuning>,
     time: Res<Time>,
 ) {
-    match velocities.get_single_mut() {
-        Err(_) => {
-            // The player has not been spawned yet (or the level might be changing)
+    let max_forward_velocity = tuning.player_max_forward_velocity;
+
+    for (mut vel, transform, player) in velocities.iter_mut() {
+        let controls = player.controls(&button_press, &player_two_button_press);
+        let forward = transform.local_x();
+        let forward_dir = Vec2::new(forward.x, forward.y);
+        let relative_forward_velocity = forward_dir.dot(vel.linvel);
+        let mut intensity = 0.0;
+        if controls.left_pressed && relative_forward_velocity > PLAYER_BRAKE_MIN_VELOCITY {
+            // Slow down until the player halts; contribute 33% more when fighting a boost
+            let brake_multiplier = if controls.right_pressed { 1.33 } else { 1.0 };
+            intensity += max_forward_velocity * PLAYER_SLOW_DOWN_VELOCITY_RATIO * brake_multiplier;
         }
-        Ok((mut vel, transform)) => {
-            let forward = transform.local_x();
-            let forward_dir = Vec2::new(forward.x, forward.y);
-            let relative_forward_velocity = forward_dir.dot(vel.linvel);
-            let mut intensity = 0.0;
-            if button_press.left_pressed && relative_forward_velocity > 15.0 {
-                // Slow down until the player halts; contribute 33% more when fighting a boost
-                let brake_multiplier = if button_press.right_pressed {
-                    1.33
-                } else {
-                    1.0
-                };
-                intensity += PLAYER_SLOW_DOWN_VELOCITY * brake_multiplier;
-            }
-            if button_press.right_pressed && relative_forward_velocity < PLAYER_MAX_FORWARD_VELOCITY
-            {
-                // Accelerate in the forward direction
-                intensity += PLAYER_MAX_FORWARD_VELOCITY;
-            }
-            let player_control_force = forward_dir * intensity;
-
-            let translation_2d: Vec2 = Vec2::new(transform.translation.x, transform.translation.y);
-            let dir_from_gravity_source = translation_2d.normalize();
-            let dot = forward_dir.dot(dir_from_gravity_source);
-            // maintain a right angle between player movement direction and gravity source direction
-            // TODO: slow down when close to the target (0 dot product)
-            let angular_velocity = if dot > 0.05 {
-                PLAYER_MAX_ANGULAR_VELOCITY
-            } else if dot < -0.05 {
-                -PLAYER_MAX_ANGULAR_VELOCITY
-            } else {
-                0.0
-            };
-
-            vel.linvel += player_control_force * time.delta_secs();
-            vel.angvel = (angular_velocity * time.delta_secs())
-                .clamp(-PLAYER_MAX_ANGULAR_VELOCITY, PLAYER_MAX_ANGULAR_VELOCITY);
+        if controls.right_pressed && relative_forward_velocity < max_forward_velocity {
+            // Accelerate in the forward direction
+            intensity += max_forward_velocity;
         }
+        let player_control_force = forward_dir * intensity;
+
+        let translation_2d: Vec2 = Vec2::new(transform.translation.x, transform.translation.y);
+        let up = transform.local_y();
+        let up_dir = Vec2::new(up.x, up.y);
+        // the player stands on the terrain, so "up" points towards the gravity source
+        let target_up_dir = -translation_2d.normalize_or_zero();
+        let angle_error = signed_angle_between(up_dir, target_up_dir);
+
+        vel.linvel += player_control_force * time.delta_secs();
+        vel.angvel =
+            next_upright_angular_velocity(&tuning, angle_error, vel.angvel, time.delta_secs());
     }
 }
 
-fn update_flag_aura(mut aura_query: Query<(&mut Path, &mut Stroke, &mut Collider, &mut FlagAura)>) {
+pub fn update_flag_aura(mut aura_query: Query<(&mut Transform, &mut Ring), With<FlagAura>>) {
     let min_radius = 0.0;
     let max_radius = 100.0;
     let hue = 35.0;
@@ -778,63 +1106,120 @@ fn update_flag_aura(mut aura_query: Query<(&mut Path, &mut Stroke, &mut Collider
         Err(_) => {
             // Aura should always exist at this phase
         }
-        Ok((mut path, mut stroke, mut collider, mut aura)) => {
+        Ok((mut transform, mut ring)) => {
             let (next_radius, next_color) =
-                update_ring(aura.0, radius_delta, min_radius, max_radius, hue);
+                update_ring(transform.scale.x, radius_delta, min_radius, max_radius, hue);
 
-            let next_shape = shapes::Circle {
-                radius: next_radius,
-                center: Vec2::ZERO,
-            };
-
-            aura.0 = next_radius;
-            stroke.color = next_color;
-            *path = ShapePath::build_as(&next_shape);
-
-            let next_collider = Collider::ball(next_radius);
-            *collider = next_collider;
+            transform.scale = ring_scale(next_radius);
+            ring.color = next_color;
         }
     }
 }
 
+// Of the players touching the flag (and their distance to it), the closest one takes the
+// level. Player one takes it if both are exactly as close.
+pub fn goal_winner(touching_players: impl IntoIterator<Item = (Player, f32)>) -> Option<Player> {
+    touching_players
+        .into_iter()
+        .min_by(|(player, distance), (other_player, other_distance)| {
+            distance
+                .total_cmp(other_distance)
+                .then(player.cmp(other_player))
+        })
+        .map(|(player, _)| player)
+}
+
 fn check_goal_reached(
-    player_query: Query<(&Transform, &Collider), With<Player>>,
-    flag_query: Query<Entity, With<Flag>>,
+    player_query: Query<(&Transform, &Collider, &Player)>,
+    flag_query: Query<(Entity, &Transform), With<Flag>>,
     mut game_event: EventWriter<GameEvent>,
     mut app_state: ResMut<NextState<AppState>>,
+    game_level: Res<GameLevel>,
+    game_mode: Res<GameMode>,
     rapier_context: ReadDefaultRapierContext,
 ) {
-    if let (Ok((player_transform, player_shape)), Ok(flag_entity)) =
-        (player_query.get_single(), flag_query.get_single())
-    {
-        let shape_pos: Vec2 = Vec2::new(
-            player_transform.translation.x,
-            player_transform.translation.y,
-        );
-        let (_, player_angle) = player_transform.rotation.to_axis_angle();
-        let flag_id = flag_entity.index();
-
-        rapier_context.intersections_with_shape(
-            shape_pos,
-            player_angle,
-            player_shape,
-            QueryFilter::predicate(QueryFilter::only_fixed(), &|entity: Entity| {
-                entity.index() == flag_id
-            }),
-            |_| {
-                game_event.send(GameEvent::GoalReached);
-                app_state.set(AppState::LoadingLevel);
+    let Ok((flag_entity, flag_transform)) = flag_query.get_single() else {
+        return;
+    };
+    let flag_id = flag_entity.index();
 
-                true
-            },
-        );
+    let touching_players = player_query
+        .iter()
+        .filter(|(player_transform, player_shape, _)| {
+            let shape_pos: Vec2 = Vec2::new(
+                player_transform.translation.x,
+                player_transform.translation.y,
+            );
+            let (_, player_angle) = player_transform.rotation.to_axis_angle();
+            let mut touches_flag = false;
+
+            rapier_context.intersections_with_shape(
+                shape_pos,
+                player_angle,
+                player_shape,
+                QueryFilter::predicate(QueryFilter::only_fixed(), &|entity: Entity| {
+                    entity.index() == flag_id
+                }),
+                |_| {
+                    touches_flag = true;
+
+                    false
+                },
+            );
+
+            touches_flag
+        })
+        .map(|(player_transform, _, player)| {
+            let distance = player_transform
+                .translation
+                .truncate()
+                .distance(flag_transform.translation.truncate());
+
+            (*player, distance)
+        });
+
+    if let Some(player) = goal_winner(touching_players) {
+        game_event.send(GameEvent::GoalReached(player));
+
+        // Modes with a fixed amount of levels end the run after the final level
+        match game_mode.final_level() {
+            Some(final_level_n) if game_level.n >= final_level_n => {
+                app_state.set(AppState::GameOver);
+            }
+            _ => app_state.set(AppState::LoadingLevel),
+        }
+    }
+}
+
+// Runs right after the goal is reached, while the flag still exists
+fn celebrate_goal(
+    mut game_event: EventReader<GameEvent>,
+    mut particle_bursts: EventWriter<ParticleBurst>,
+    flag_query: Query<&Transform, With<Flag>>,
+) {
+    let Ok(flag_transform) = flag_query.get_single() else {
+        return;
+    };
+
+    for event in game_event.read() {
+        if let GameEvent::GoalReached(player) = event {
+            particle_bursts.send(ParticleBurst {
+                effect: goal_celebration_effect(*player),
+                position: flag_transform.translation.truncate(),
+                direction: flag_transform.local_y().truncate(),
+                velocity: Vec2::ZERO,
+            });
+        }
     }
 }
 
 fn detect_player_collision(
     mut contact_force_events: EventReader<ContactForceEvent>,
     mut game_event: EventWriter<GameEvent>,
+    mut particle_bursts: EventWriter<ParticleBurst>,
     mut player_collision: ResMut<PlayerCollision>,
+    player_query: Query<&Transform, With<Player>>,
+    rapier_context: ReadDefaultRapierContext,
     time: Res<Time>,
 ) {
     for event in contact_force_events.read() {
@@ -848,7 +1233,110 @@ fn detect_player_collision(
                 time_since_previous_collision,
                 event.total_force_magnitude,
             ));
+
+            let spark_position = contact_point(&rapier_context, event.collider1, event.collider2)
+                .or_else(|| {
+                    player_query
+                        .get(event.collider1)
+                        .or(player_query.get(event.collider2))
+                        .ok()
+                        .map(|transform| transform.translation.truncate())
+                });
+
+            if let Some(position) = spark_position {
+                particle_bursts.send(ParticleBurst {
+                    effect: collision_sparks_effect(),
+                    position,
+                    direction: event.max_force_direction,
+                    velocity: Vec2::ZERO,
+                });
+            }
+        }
+    }
+}
+
+// Collisions between the debris (or the debris and the terrain) chip off some particles.
+// The players have sparks of their own (see `detect_player_collision`).
+fn detect_debris_impacts(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut particle_bursts: EventWriter<ParticleBurst>,
+    debris_query: Query<(&Transform, &Velocity, &ObjectDensity)>,
+    other_query: Query<(Option<&Velocity>, Has<Player>)>,
+    vv_config: Res<VVConfig>,
+    rapier_context: ReadDefaultRapierContext,
+) {
+    for event in collision_events.read() {
+        let CollisionEvent::Started(entity1, entity2, flags) = *event else {
+            continue;
+        };
+
+        if flags.contains(CollisionEventFlags::SENSOR) {
+            continue;
+        }
+
+        let Some((debris, other)) = [(entity1, entity2), (entity2, entity1)]
+            .into_iter()
+            .find(|(debris, _)| debris_query.contains(*debris))
+        else {
+            continue;
+        };
+        let Ok((transform, velocity, density)) = debris_query.get(debris) else {
+            continue;
+        };
+        let Ok((other_velocity, other_is_player)) = other_query.get(other) else {
+            continue;
+        };
+
+        if other_is_player {
+            continue;
         }
+
+        let other_velocity = other_velocity.map_or(Vec2::ZERO, |velocity| velocity.linvel);
+        let impact_velocity = velocity.linvel - other_velocity;
+
+        if impact_velocity.length() < DEBRIS_IMPACT_MIN_VELOCITY {
+            continue;
+        }
+
+        particle_bursts.send(ParticleBurst {
+            effect: debris_impact_effect(density.color(vv_config.colorblind_palette)),
+            position: contact_point(&rapier_context, debris, other)
+                .unwrap_or(transform.translation.truncate()),
+            direction: impact_velocity,
+            velocity: other_velocity,
+        });
+    }
+}
+
+// The first point where the colliders touch, if they do
+fn contact_point(
+    rapier_context: &RapierContext,
+    collider1: Entity,
+    collider2: Entity,
+) -> Option<Vec2> {
+    rapier_context
+        .contact_pair(collider1, collider2)?
+        .manifolds()
+        .find_map(|manifold| {
+            manifold
+                .solver_contacts()
+                .next()
+                .map(|solver_contact| solver_contact.point())
+        })
+}
+
+fn update_player_collision_groups(
+    mut player_query: Query<&mut CollisionGroups, With<Player>>,
+    debris_collisions_off: Option<Res<DebrisCollisionsOff>>,
+) {
+    let collision_groups = if debris_collisions_off.is_some() {
+        NO_DEBRIS_COLLISION_GROUP
+    } else {
+        DEFAULT_COLLISION_GROUP
+    };
+
+    for mut player_collision_groups in player_query.iter_mut() {
+        player_collision_groups.set_if_neq(collision_groups);
     }
 }
 
@@ -912,8 +1400,8 @@ fn countdown_text_update(
     }
 }
 
-fn update_gravity_visuals(
-    mut visuals_query: Query<(&mut Path, &mut Stroke, &mut GravityRing)>,
+pub fn update_gravity_visuals(
+    mut visuals_query: Query<(&mut Transform, &mut Ring), With<GravityRing>>,
     gravity_source: Res<GravitySource>,
 ) {
     let level_bounds_radius_pixels = LEVEL_BOUNDS_RADIUS_METERS * PIXELS_PER_METER;
@@ -925,104 +1413,123 @@ fn update_gravity_visuals(
     let radius_delta = gravity_source.force * radius_force_ratio;
     let hue = 220.0;
 
-    for (mut path, mut stroke, mut ring) in visuals_query.iter_mut() {
+    for (mut transform, mut ring) in visuals_query.iter_mut() {
         let (next_radius, next_color) =
-            update_ring(ring.0, radius_delta, min_radius, max_radius, hue);
-        let next_shape = shapes::Circle {
-            radius: next_radius,
-            center: Vec2::ZERO,
-        };
+            update_ring(transform.scale.x, radius_delta, min_radius, max_radius, hue);
 
-        ring.0 = next_radius;
-        stroke.color = next_color;
-        *path = ShapePath::build_as(&next_shape);
+        transform.scale = ring_scale(next_radius);
+        ring.color = next_color;
     }
 }
 
-fn spawn_accel_particles(
-    mut commands: Commands,
-    mut spawn_timer: ResMut<ParticleSpawnTimer>,
-    player_query: Query<(&Transform, &Velocity), With<Player>>,
+fn update_player_emitters(
+    player_query: Query<(&Transform, &Velocity, &Player, &Children)>,
+    mut emitter_query: Query<(&mut ParticleEmitter, Has<BrakeDustEmitter>)>,
     button_press: Res<ButtonPress>,
-    time: Res<Time>,
+    player_two_button_press: Res<PlayerTwoButtonPress>,
 ) {
-    spawn_timer.0.tick(time.delta());
-
-    if !spawn_timer.0.just_finished() || !button_press.right_pressed {
-        return;
+    for (transform, velocity, player, children) in player_query.iter() {
+        let controls = player.controls(&button_press, &player_two_button_press);
+        let relative_forward_velocity = transform.local_x().truncate().dot(velocity.linvel);
+        let braking =
+            controls.left_pressed && relative_forward_velocity > PLAYER_BRAKE_MIN_VELOCITY;
+
+        for child in children.iter() {
+            if let Ok((mut emitter, is_brake_dust)) = emitter_query.get_mut(*child) {
+                emitter.active = if is_brake_dust {
+                    braking
+                } else {
+                    controls.right_pressed
+                };
+            }
+        }
     }
+}
 
-    let Ok((transform, velocity)) = player_query.get_single() else {
-        return;
-    };
-
-    let mut rng = thread_rng();
-    let forward = transform.local_x();
-    let forward_dir = Vec2::new(forward.x, forward.y);
-    let backward_dir = -forward_dir;
-    let perpendicular = Vec2::new(-forward_dir.y, forward_dir.x);
-
-    let player_half_width = PLAYER_WIDTH_METERS * PIXELS_PER_METER * 0.5;
-    let spawn_pos = Vec2::new(transform.translation.x, transform.translation.y)
-        + backward_dir * player_half_width;
-
-    let particle_count = rng.gen_range(1..=2);
-    for _ in 0..particle_count {
-        let radius = rng.gen_range(ACCEL_PARTICLE_RADIUS_MIN..=ACCEL_PARTICLE_RADIUS_MAX);
-        let hue = rng.gen_range(25.0..45.0);
-        let spread = rng.gen_range(-ACCEL_PARTICLE_SPREAD..ACCEL_PARTICLE_SPREAD);
-        let particle_velocity = backward_dir * ACCEL_PARTICLE_BASE_SPEED
-            + velocity.linvel * 0.3
-            + perpendicular * spread;
-
-        let shape = shapes::Circle {
-            radius,
-            center: Vec2::ZERO,
-        };
-
-        commands.spawn((
-            ShapeBundle {
-                path: GeometryBuilder::build_as(&shape),
-                transform: Transform::from_translation(Vec3::new(
-                    spawn_pos.x,
-                    spawn_pos.y,
-                    Z_INDEX_PARTICLES,
-                )),
-                ..Default::default()
-            },
-            Fill {
-                options: FillOptions::default(),
-                color: Color::hsla(hue, 1.0, 0.7, 0.9),
-            },
-            GameObject,
-            AccelParticle {
-                lifetime: Timer::from_seconds(ACCEL_PARTICLE_LIFETIME, TimerMode::Once),
-                velocity: particle_velocity,
-                hue,
-            },
-        ));
+// Particle effects
+
+fn exhaust_effect(tuning: &Tuning) -> ParticleEffect {
+    let base_speed = tuning.accel_particle_base_speed;
+    let color = Color::hsla(35.0, 1.0, 0.7, 0.9);
+
+    ParticleEffect {
+        shape: ParticleShape::Circle,
+        gradient: (color, color.with_alpha(0.0)),
+        hue_variation: 10.0,
+        lifetime: tuning.accel_particle_lifetime,
+        amount: 1..=2,
+        radius: ACCEL_PARTICLE_RADIUS_MIN..=ACCEL_PARTICLE_RADIUS_MAX,
+        speed: base_speed..=base_speed.hypot(tuning.accel_particle_spread),
+        // The spread is a sideways speed
+        spread: tuning.accel_particle_spread.atan2(base_speed),
+        inherited_velocity: 0.3,
+        gravity_ratio: 1.0,
     }
 }
 
-fn update_accel_particles(
-    mut commands: Commands,
-    mut particle_query: Query<(Entity, &mut Transform, &mut Fill, &mut AccelParticle)>,
-    time: Res<Time>,
-) {
-    for (entity, mut transform, mut fill, mut particle) in particle_query.iter_mut() {
-        particle.lifetime.tick(time.delta());
+fn brake_dust_effect() -> ParticleEffect {
+    let color = Color::hsla(25.0, 0.3, 0.6, 0.6);
+
+    ParticleEffect {
+        shape: ParticleShape::Circle,
+        gradient: (color, color.with_alpha(0.0)),
+        hue_variation: 5.0,
+        lifetime: 0.6,
+        amount: 1..=3,
+        radius: 2.0..=5.0,
+        speed: 15.0..=45.0,
+        spread: 0.5,
+        inherited_velocity: 0.2,
+        gravity_ratio: 0.5,
+    }
+}
 
-        if particle.lifetime.finished() {
-            commands.entity(entity).despawn();
-            continue;
-        }
+fn collision_sparks_effect() -> ParticleEffect {
+    ParticleEffect {
+        shape: ParticleShape::Square,
+        gradient: (
+            VetovoimaColor::WHITEISH,
+            VetovoimaColor::REDDISH.with_alpha(0.0),
+        ),
+        hue_variation: 10.0,
+        lifetime: 0.35,
+        amount: 8..=14,
+        radius: 1.0..=2.5,
+        speed: 80.0..=220.0,
+        spread: PI,
+        inherited_velocity: 0.0,
+        gravity_ratio: 1.5,
+    }
+}
 
-        let delta = time.delta_secs();
-        transform.translation.x += particle.velocity.x * delta;
-        transform.translation.y += particle.velocity.y * delta;
+fn debris_impact_effect(debris_color: Color) -> ParticleEffect {
+    ParticleEffect {
+        shape: ParticleShape::Square,
+        gradient: (debris_color.with_alpha(0.8), debris_color.with_alpha(0.0)),
+        hue_variation: 0.0,
+        lifetime: 0.5,
+        amount: 3..=6,
+        radius: 1.5..=3.5,
+        speed: 30.0..=90.0,
+        spread: 1.0,
+        inherited_velocity: 1.0,
+        gravity_ratio: 0.5,
+    }
+}
 
-        let remaining = particle.lifetime.remaining_secs() / ACCEL_PARTICLE_LIFETIME;
-        fill.color = Color::hsla(particle.hue, 1.0, 0.7, remaining * 0.9);
+fn goal_celebration_effect(player: Player) -> ParticleEffect {
+    ParticleEffect {
+        shape: ParticleShape::Square,
+        gradient: (player.color(), VetovoimaColor::WHITEISH.with_alpha(0.0)),
+        // Confetti
+        hue_variation: 60.0,
+        lifetime: 1.2,
+        amount: 40..=60,
+        radius: 2.0..=4.5,
+        speed: 60.0..=260.0,
+        spread: PI,
+        inherited_velocity: 0.0,
+        gravity_ratio: 0.3,
     }
 }
 
@@ -1039,6 +1546,19 @@ fn timer_to_secs_remaining(timer: &Timer) -> u32 {
     }
 }
 
+// The angle (-PI..=PI) that rotates "from" onto "to", positive is counterclockwise.
+// Unlike a plain dot product this can tell upright and upside down apart.
+pub fn signed_angle_between(from: Vec2, to: Vec2) -> f32 {
+    if from == Vec2::ZERO || to == Vec2::ZERO {
+        return 0.0;
+    }
+
+    let cross = from.x * to.y - from.y * to.x;
+    let dot = from.dot(to);
+
+    cross.atan2(dot)
+}
+
 fn update_ring(
     current_radius: f32,
     radius_delta: f32,
@@ -1068,8 +1588,7 @@ fn update_ring(
     (next_radius, next_color)
 }
 
-fn random_external_force(max_force: f32, max_torque: f32) -> ExternalForce {
-    let mut rng = thread_rng();
+fn random_external_force(max_force: f32, max_torque: f32, rng: &mut impl Rng) -> ExternalForce {
     let force_abs = max_force.abs();
 
     let x = rng.gen_range(-force_abs..force_abs);
@@ -1,40 +1,186 @@
 use bevy::prelude::*;
-use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
 
-use crate::app::{AppState, UiConfig, VetovoimaColor};
+use crate::{
+    app::{
+        read_save_file, save_file_path, write_save_file, AppState, ButtonPress, GameMode, UiConfig,
+        VetovoimaColor,
+    },
+    attract_mode::AttractMode,
+    stats::RunStats,
+    time_trial::format_time,
+};
 
-const GAME_OVER_SCREEN_SHOW_DURATION_SECONDS: u64 = 5;
+const LEVEL_BESTS_FILENAME: &str = "VVLevelBests.toml";
+// The summary scrolls when there are more levels than this
+const SUMMARY_VISIBLE_ROWS: usize = 8;
+// Nobody dismisses the summary during a demo
+const DEMO_GAME_OVER_SCREEN_SHOW_DURATION_SECONDS: u64 = 5;
 
-#[derive(Component, Resource)]
-struct GameOverScreen(Timer);
+// Best scores of each level, keyed by the level seed (as in the stats). The seed is derived
+// from the run seed (or the date) and the level number, so the same level always has the
+// same key.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+struct LevelBests {
+    scores: BTreeMap<String, u32>,
+}
+
+// The endless levels are random, so they're never played again
+fn has_level_bests(game_mode: &GameMode) -> bool {
+    matches!(game_mode, GameMode::DailyChallenge(_) | GameMode::Seeded(_))
+}
+
+struct SummaryRow {
+    label: String,
+    is_personal_best: bool,
+}
+
+#[derive(Resource, Default)]
+struct RunSummary {
+    rows: Vec<SummaryRow>,
+    selected_row: usize,
+    first_visible_row: usize,
+}
+
+impl RunSummary {
+    fn select_row(&mut self, step: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+
+        let last_row = self.rows.len() - 1;
+
+        self.selected_row =
+            (self.selected_row as isize + step).clamp(0, last_row as isize) as usize;
+
+        // Keep the selected row visible
+        if self.selected_row < self.first_visible_row {
+            self.first_visible_row = self.selected_row;
+        } else if self.selected_row >= self.first_visible_row + SUMMARY_VISIBLE_ROWS {
+            self.first_visible_row = self.selected_row + 1 - SUMMARY_VISIBLE_ROWS;
+        }
+    }
+}
+
+#[derive(Resource)]
+struct DemoGameOverTimer(Timer);
 
 #[derive(Component)]
-struct GameOverTitle;
+struct GameOverScreen;
+
+// One of the visible rows of the summary (the index is the position on the screen)
+#[derive(Component)]
+struct SummaryRowText(usize);
 
 pub struct GameOverPlugin;
 
 impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::GameOver), gameover_screen_setup)
+        let level_bests: LevelBests =
+            read_save_file(&save_file_path(LEVEL_BESTS_FILENAME)).unwrap_or_default();
+
+        app.insert_resource(level_bests)
+            .add_systems(
+                OnEnter(AppState::GameOver),
+                gameover_screen_setup.run_if(has_game_over_screen),
+            )
             .add_systems(
                 Update,
-                gameover_screen_update.run_if(in_state(AppState::GameOver)),
+                (gameover_screen_navigation, summary_rows_update)
+                    .chain()
+                    .run_if(in_state(AppState::GameOver))
+                    .run_if(has_game_over_screen),
+            )
+            .add_systems(
+                Update,
+                demo_gameover_screen_update
+                    .run_if(in_state(AppState::GameOver))
+                    .run_if(resource_exists::<DemoGameOverTimer>),
             )
             .add_systems(OnExit(AppState::GameOver), gameover_screen_cleanup);
     }
 }
 
+// Time trial and versus have their own results screens
+fn has_game_over_screen(game_mode: Res<GameMode>) -> bool {
+    matches!(
+        *game_mode,
+        GameMode::Endless | GameMode::DailyChallenge(_) | GameMode::Seeded(_)
+    )
+}
+
 fn gameover_screen_setup(
     mut commands: Commands,
+    mut level_bests: ResMut<LevelBests>,
+    run_stats: Res<RunStats>,
+    game_mode: Res<GameMode>,
+    attract_mode: Option<Res<AttractMode>>,
     asset_server: Res<AssetServer>,
     ui_config: Res<UiConfig>,
 ) {
-    commands.insert_resource(GameOverScreen(Timer::new(
-        Duration::from_secs(GAME_OVER_SCREEN_SHOW_DURATION_SECONDS),
-        TimerMode::Once,
-    )));
+    let is_demo = attract_mode.is_some();
+    // The demo doesn't set records
+    let keeps_bests = has_level_bests(&game_mode) && !is_demo;
+    let mut rows: Vec<SummaryRow> = Vec::new();
+    let mut has_new_bests = false;
+
+    for level_stats in run_stats.levels.iter().filter(|level| level.reached_goal) {
+        let score = level_stats.score();
+        let mut is_personal_best = false;
+
+        if keeps_bests {
+            let best_score = level_bests
+                .scores
+                .entry(level_stats.seed.clone())
+                .or_default();
+
+            if score > *best_score {
+                *best_score = score;
+                is_personal_best = true;
+                has_new_bests = true;
+            }
+        }
+
+        rows.push(SummaryRow {
+            label: format!(
+                "Level {}  {}  {} hits  {} pts",
+                level_stats.level,
+                format_time(level_stats.secs),
+                level_stats.collisions,
+                score
+            ),
+            is_personal_best,
+        });
+    }
+
+    if has_new_bests {
+        write_save_file(&save_file_path(LEVEL_BESTS_FILENAME), &*level_bests);
+    }
+
+    if is_demo {
+        commands.insert_resource(DemoGameOverTimer(Timer::new(
+            Duration::from_secs(DEMO_GAME_OVER_SCREEN_SHOW_DURATION_SECONDS),
+            TimerMode::Once,
+        )));
+    }
+
+    let visible_rows = rows.len().min(SUMMARY_VISIBLE_ROWS);
+    let total_label = if rows.is_empty() {
+        "No levels completed".to_string()
+    } else {
+        format!("Total {} pts", run_stats.score())
+    };
+
+    commands.insert_resource(RunSummary { rows, ..default() });
 
     let font = asset_server.load(ui_config.font_filename);
+    let text_font = TextFont {
+        font: font.clone(),
+        font_size: ui_config.font_size_countdown,
+        ..Default::default()
+    };
 
     commands
         .spawn((
@@ -43,10 +189,11 @@ fn gameover_screen_setup(
                 height: Val::Percent(100.0),
                 justify_content: JustifyContent::Center,
                 align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
                 ..default()
             },
             BackgroundColor(VetovoimaColor::BLACKISH),
-            GameOverTitle,
+            GameOverScreen,
         ))
         .with_children(|container| {
             container.spawn((
@@ -58,28 +205,102 @@ fn gameover_screen_setup(
                 },
                 TextColor(VetovoimaColor::REDDISH),
             ));
+
+            // The contents are filled in by summary_rows_update
+            for row_index in 0..visible_rows {
+                container.spawn((
+                    Text::new(""),
+                    text_font.clone(),
+                    TextColor(VetovoimaColor::WHITEISH),
+                    SummaryRowText(row_index),
+                ));
+            }
+
+            container.spawn((
+                Text::new(total_label),
+                text_font.clone(),
+                TextColor(VetovoimaColor::WHITEISH),
+            ));
+
+            container.spawn((
+                Text::new("Up/down to scroll, enter to continue"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: ui_config.font_size_body_small,
+                    ..Default::default()
+                },
+                TextColor(VetovoimaColor::BLUEISH_MID),
+            ));
         });
 }
 
-fn gameover_screen_cleanup(
-    mut commands: Commands,
-    title_query: Query<Entity, With<GameOverTitle>>,
+fn gameover_screen_navigation(
+    button_press: Res<ButtonPress>,
+    mut run_summary: ResMut<RunSummary>,
+    mut app_state: ResMut<NextState<AppState>>,
 ) {
-    commands.remove_resource::<GameOverScreen>();
+    if !button_press.is_changed() {
+        return;
+    }
 
-    for object in title_query.iter() {
-        commands.entity(object).despawn_recursive();
+    if button_press.main_control_pressed || button_press.select_pressed {
+        app_state.set(AppState::InMenu);
+    } else if button_press.up_pressed {
+        run_summary.select_row(-1);
+    } else if button_press.down_pressed {
+        run_summary.select_row(1);
+    }
+}
+
+fn summary_rows_update(
+    mut row_text_query: Query<(&mut Text, &mut TextColor, &SummaryRowText)>,
+    run_summary: Res<RunSummary>,
+) {
+    if !run_summary.is_changed() {
+        return;
+    }
+
+    for (mut text, mut text_color, row_text) in row_text_query.iter_mut() {
+        let row_index = run_summary.first_visible_row + row_text.0;
+        let Some(row) = run_summary.rows.get(row_index) else {
+            **text = String::new();
+            continue;
+        };
+        let is_selected = row_index == run_summary.selected_row;
+        let marker = if is_selected { "> " } else { "  " };
+        let personal_best_label = if row.is_personal_best { "  PB" } else { "" };
+
+        **text = format!("{}{}{}", marker, row.label, personal_best_label);
+        *text_color = TextColor(if is_selected {
+            VetovoimaColor::BLUEISH_LIGHT
+        } else if row.is_personal_best {
+            VetovoimaColor::YELLOWISH
+        } else {
+            VetovoimaColor::WHITEISH
+        });
     }
 }
 
-fn gameover_screen_update(
-    mut game_over_screen: ResMut<GameOverScreen>,
+fn demo_gameover_screen_update(
+    mut demo_timer: ResMut<DemoGameOverTimer>,
     mut app_state: ResMut<NextState<AppState>>,
     time: Res<Time>,
 ) {
-    game_over_screen.0.tick(time.delta());
+    demo_timer.0.tick(time.delta());
 
-    if game_over_screen.0.finished() {
+    if demo_timer.0.finished() {
         app_state.set(AppState::InMenu);
     }
 }
+
+fn gameover_screen_cleanup(
+    mut commands: Commands,
+    screen_query: Query<Entity, With<GameOverScreen>>,
+) {
+    commands.remove_resource::<RunSummary>();
+    commands.remove_resource::<DemoGameOverTimer>();
+
+    for object in screen_query.iter() {
+        commands.entity(object).despawn_recursive();
+    }
+}
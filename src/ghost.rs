@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, time::Duration};
+
+use crate::{
+    app::{read_save_file, save_file_path, write_save_file, AppState, GameMode, PIXELS_PER_METER},
+    game::{GameLevel, Player, PLAYER_HEIGHT_METERS, PLAYER_WIDTH_METERS, Z_INDEX_OBJECTS},
+    main_menu::MenuEvent,
+    time_trial::TimeTrialRun,
+};
+
+const GHOST_RECORD_FILENAME: &str = "VVTimeTrialGhost.toml";
+const GHOST_SAMPLE_INTERVAL_SECONDS: f32 = 0.05;
+const GHOST_ALPHA: f32 = 0.35;
+
+// Player position (x, y) and rotation (radians) at a point in time
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct GhostFrame(f32, f32, f32);
+
+impl GhostFrame {
+    fn from_transform(transform: &Transform) -> Self {
+        let (_, _, angle) = transform.rotation.to_euler(EulerRot::XYZ);
+
+        GhostFrame(transform.translation.x, transform.translation.y, angle)
+    }
+
+    fn lerp(&self, next: &GhostFrame, t: f32) -> (Vec2, Quat) {
+        let position = Vec2::new(self.0, self.1).lerp(Vec2::new(next.0, next.1), t);
+        let rotation = Quat::from_rotation_z(self.2).slerp(Quat::from_rotation_z(next.2), t);
+
+        (position, rotation)
+    }
+}
+
+// The player movement of the fastest completed time trial run, sampled per level
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+struct GhostRecord {
+    total_secs: f32,
+    levels: Vec<Vec<GhostFrame>>,
+}
+
+#[derive(Resource, Default)]
+struct GhostRecording {
+    level_elapsed: Duration,
+    level_frames: Vec<GhostFrame>,
+    levels: Vec<Vec<GhostFrame>>,
+}
+
+#[derive(Component)]
+struct Ghost {
+    level_index: usize,
+}
+
+#[derive(Default)]
+pub struct GhostPlugin {
+    // A ghost file to race against, in place of the personal best
+    pub replay_path: Option<PathBuf>,
+}
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        let record: GhostRecord = match &self.replay_path {
+            Some(replay_path) => read_save_file(replay_path).unwrap_or_else(|| {
+                eprintln!("Could not read the replay {}", replay_path.display());
+
+                GhostRecord::default()
+            }),
+            None => read_save_file(&save_file_path(GHOST_RECORD_FILENAME)).unwrap_or_default(),
+        };
+
+        app.insert_resource(record)
+            .insert_resource(GhostRecording::default())
+            .add_systems(Update, begin_recording)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (reset_level_recording, spawn_ghost).run_if(resource_equals(GameMode::TimeTrial)),
+            )
+            .add_systems(
+                Update,
+                (record_player, replay_ghost)
+                    .chain()
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(resource_equals(GameMode::TimeTrial)),
+            )
+            .add_systems(
+                OnExit(AppState::InGame),
+                (
+                    finish_level_recording.run_if(resource_equals(GameMode::TimeTrial)),
+                    ghost_cleanup,
+                ),
+            );
+
+        // Racing against a replay doesn't touch the personal best
+        if self.replay_path.is_none() {
+            app.add_systems(
+                OnEnter(AppState::GameOver),
+                save_ghost.run_if(resource_equals(GameMode::TimeTrial)),
+            );
+        }
+    }
+}
+
+fn begin_recording(mut menu_event: EventReader<MenuEvent>, mut recording: ResMut<GhostRecording>) {
+    for event in menu_event.read() {
+        if let MenuEvent::BeginNewGame(GameMode::TimeTrial) = event {
+            *recording = GhostRecording::default();
+        }
+    }
+}
+
+fn reset_level_recording(mut recording: ResMut<GhostRecording>) {
+    recording.level_elapsed = Duration::ZERO;
+    recording.level_frames.clear();
+}
+
+fn spawn_ghost(mut commands: Commands, record: Res<GhostRecord>, game_level: Res<GameLevel>) {
+    let level_index = game_level.n.saturating_sub(1) as usize;
+    let Some(first_frame) = record
+        .levels
+        .get(level_index)
+        .and_then(|frames| frames.first())
+    else {
+        return;
+    };
+    let position = Vec2::new(first_frame.0, first_frame.1);
+    let rotation = Quat::from_rotation_z(first_frame.2);
+
+    // Only drawn; the ghost has no rigid body or collider, so the simulation never sees it.
+    // It isn't a `Player` either: the controls, the goal check, the collision detection, the
+    // bot and the recording of the ghost itself all take every `Player` for a real one.
+    commands.spawn((
+        ShapeBundle {
+            path: GeometryBuilder::build_as(&shapes::Rectangle {
+                extents: Vec2::new(
+                    PLAYER_WIDTH_METERS * PIXELS_PER_METER,
+                    PLAYER_HEIGHT_METERS * PIXELS_PER_METER,
+                ),
+                origin: RectangleOrigin::Center,
+                ..Default::default()
+            }),
+            // Drawn just below the player and other objects
+            transform: Transform::from_translation(position.extend(Z_INDEX_OBJECTS - 0.1))
+                .with_rotation(rotation),
+            ..Default::default()
+        },
+        Fill {
+            options: FillOptions::default(),
+            color: Player::One.color().with_alpha(GHOST_ALPHA),
+        },
+        Ghost { level_index },
+    ));
+}
+
+fn record_player(
+    player_query: Query<&Transform, With<Player>>,
+    mut recording: ResMut<GhostRecording>,
+    time: Res<Time>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let sample_interval = Duration::from_secs_f32(GHOST_SAMPLE_INTERVAL_SECONDS);
+
+    // The first frame is recorded right away, the rest at a fixed interval
+    if recording.level_frames.len() as u32 * sample_interval <= recording.level_elapsed {
+        recording
+            .level_frames
+            .push(GhostFrame::from_transform(player_transform));
+    }
+
+    recording.level_elapsed += time.delta();
+}
+
+fn replay_ghost(
+    mut ghost_query: Query<(&mut Transform, &Ghost)>,
+    recording: Res<GhostRecording>,
+    record: Res<GhostRecord>,
+) {
+    let Ok((mut transform, ghost)) = ghost_query.get_single_mut() else {
+        return;
+    };
+    let Some(frames) = record.levels.get(ghost.level_index) else {
+        return;
+    };
+    let Some(last_frame_index) = frames.len().checked_sub(1) else {
+        return;
+    };
+
+    let frame_position = recording.level_elapsed.as_secs_f32() / GHOST_SAMPLE_INTERVAL_SECONDS;
+    // The ghost stays put after its run is over
+    let frame_index = (frame_position.floor() as usize).min(last_frame_index);
+    let next_frame_index = (frame_index + 1).min(last_frame_index);
+    let (position, rotation) =
+        frames[frame_index].lerp(&frames[next_frame_index], frame_position.fract());
+
+    transform.translation = position.extend(transform.translation.z);
+    transform.rotation = rotation;
+}
+
+fn finish_level_recording(mut recording: ResMut<GhostRecording>, run: Res<TimeTrialRun>) {
+    // Only levels that were finished are kept
+    if run.level_times.len() > recording.levels.len() {
+        let level_frames = std::mem::take(&mut recording.level_frames);
+
+        recording.levels.push(level_frames);
+    }
+}
+
+fn save_ghost(
+    mut record: ResMut<GhostRecord>,
+    recording: Res<GhostRecording>,
+    run: Res<TimeTrialRun>,
+) {
+    let total_secs = run.total_elapsed().as_secs_f32();
+    let is_fastest = record.levels.is_empty() || total_secs < record.total_secs;
+
+    if run.is_complete() && is_fastest {
+        *record = GhostRecord {
+            total_secs,
+            levels: recording.levels.clone(),
+        };
+        write_save_file(&save_file_path(GHOST_RECORD_FILENAME), &*record);
+    }
+}
+
+fn ghost_cleanup(mut commands: Commands, ghost_query: Query<Entity, With<Ghost>>) {
+    for ghost in ghost_query.iter() {
+        commands.entity(ghost).despawn();
+    }
+}
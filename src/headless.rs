@@ -0,0 +1,158 @@
+use bevy::{prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy, window::PrimaryWindow};
+use bevy_rapier2d::prelude::*;
+use std::time::Duration;
+
+use crate::{
+    app::{AppState, ButtonPress, GameMode, PlayerTwoButtonPress, UiConfig},
+    bot::BotPlugin,
+    config::VVConfig,
+    game::{GameEvent, GameLevel, GamePlugin, LevelJump, Player},
+    simulation::{GravitySource, SimulationPlugin},
+};
+
+// Every update advances the game by exactly this much, regardless of the wall clock
+pub const HEADLESS_TIMESTEP_SECONDS: f32 = 1.0 / 60.0;
+// The loading screen is shown for a few seconds before every level
+const MAX_LOADING_TICKS: u32 = 60 * 10;
+
+// Game events of every tick so far (the event queue itself only holds the latest ones)
+#[derive(Resource, Default)]
+struct GameEventLog(Vec<GameEvent>);
+
+// The game without a window, audio or menus, for tests and tools. The game systems and
+// the physics are the same ones the actual game runs.
+pub struct HeadlessGame {
+    app: App,
+}
+
+impl HeadlessGame {
+    pub fn new(game_mode: GameMode) -> Self {
+        let mut app = App::new();
+
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            AssetPlugin::default(),
+            StatesPlugin,
+        ))
+        // Shapes and UI text are still created, even though nothing is drawn
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<Font>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            HEADLESS_TIMESTEP_SECONDS,
+        )))
+        .add_plugins((
+            RapierPhysicsPlugin::<NoUserData>::default(),
+            SimulationPlugin,
+            GamePlugin,
+            BotPlugin,
+        ))
+        .insert_resource(ButtonPress::default())
+        .insert_resource(PlayerTwoButtonPress::default())
+        .insert_resource(UiConfig::default())
+        .insert_resource(VVConfig::default())
+        .insert_resource(game_mode)
+        .insert_resource(GameEventLog::default())
+        .init_state::<AppState>()
+        .add_systems(Last, log_game_events);
+
+        // The game hides the cursor of the primary window
+        app.world_mut().spawn((Window::default(), PrimaryWindow));
+        app.update();
+
+        HeadlessGame { app }
+    }
+
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    // Loads the next level and waits until it can be played
+    pub fn start_level(&mut self) {
+        self.set_state(AppState::LoadingLevel);
+
+        for _ in 0..MAX_LOADING_TICKS {
+            if self.state() == AppState::InGame {
+                return;
+            }
+
+            self.app.update();
+        }
+
+        panic!("The level did not finish loading");
+    }
+
+    pub fn start_level_n(&mut self, level_n: u32) {
+        self.app.insert_resource(LevelJump(level_n));
+        self.start_level();
+    }
+
+    pub fn set_state(&mut self, app_state: AppState) {
+        self.app
+            .world_mut()
+            .resource_mut::<NextState<AppState>>()
+            .set(app_state);
+        self.app.update();
+    }
+
+    pub fn tick(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.app.update();
+        }
+    }
+
+    pub fn press(&mut self, button_press: ButtonPress) {
+        *self.app.world_mut().resource_mut::<ButtonPress>() = button_press;
+    }
+
+    pub fn press_player_two(&mut self, button_press: ButtonPress) {
+        self.app
+            .world_mut()
+            .resource_mut::<PlayerTwoButtonPress>()
+            .0 = button_press;
+    }
+
+    pub fn state(&self) -> AppState {
+        *self.app.world().resource::<State<AppState>>().get()
+    }
+
+    pub fn gravity_force(&self) -> f32 {
+        self.app.world().resource::<GravitySource>().force
+    }
+
+    pub fn level(&self) -> Option<&GameLevel> {
+        self.app.world().get_resource::<GameLevel>()
+    }
+
+    pub fn player(&mut self, player: Player) -> Option<(Transform, Velocity)> {
+        let world = self.app.world_mut();
+
+        world
+            .query::<(&Transform, &Velocity, &Player)>()
+            .iter(world)
+            .find(|(_, _, p)| **p == player)
+            .map(|(transform, velocity, _)| (*transform, *velocity))
+    }
+
+    pub fn events(&self) -> &[GameEvent] {
+        &self.app.world().resource::<GameEventLog>().0
+    }
+
+    pub fn clear_events(&mut self) {
+        self.app
+            .world_mut()
+            .resource_mut::<GameEventLog>()
+            .0
+            .clear();
+    }
+}
+
+fn log_game_events(mut game_event: EventReader<GameEvent>, mut log: ResMut<GameEventLog>) {
+    log.0.extend(game_event.read().cloned());
+}
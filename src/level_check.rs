@@ -0,0 +1,89 @@
+use std::fmt;
+
+use crate::{
+    app::{AppState, GameMode},
+    bot::BotDriver,
+    game::GameEvent,
+    headless::{HeadlessGame, HEADLESS_TIMESTEP_SECONDS},
+    tuning::Tuning,
+};
+
+// The countdown is the limit, but the loop is bounded (a bit past it) in case the level never
+// ends
+const EXTRA_LEVEL_SECONDS: f32 = 1.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LevelCheckOutcome {
+    // The bot reached the flag, after this many seconds
+    Solved(f32),
+    // The countdown ran out before the bot reached the flag
+    Unsolved,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LevelCheck {
+    pub seed: u64,
+    pub level_n: u32,
+    pub outcome: LevelCheckOutcome,
+}
+
+impl fmt::Display for LevelCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.outcome {
+            LevelCheckOutcome::Solved(secs) => write!(
+                f,
+                "seed {:016x} level {}: solved in {:.1}s",
+                self.seed, self.level_n, secs
+            ),
+            LevelCheckOutcome::Unsolved => write!(
+                f,
+                "seed {:016x} level {}: NOT SOLVED",
+                self.seed, self.level_n
+            ),
+        }
+    }
+}
+
+// Plays a level of `GameMode::Seeded(seed)` headlessly with the bot
+pub fn check_level(seed: u64, level_n: u32) -> LevelCheck {
+    check_level_with_tuning(seed, level_n, Tuning::default())
+}
+
+// Same as `check_level`, but with another tuning (e.g. other countdowns)
+pub fn check_level_with_tuning(seed: u64, level_n: u32, tuning: Tuning) -> LevelCheck {
+    let countdown_secs = tuning.level_countdown_secs(level_n) as f32;
+    let max_level_ticks =
+        ((countdown_secs + EXTRA_LEVEL_SECONDS) / HEADLESS_TIMESTEP_SECONDS) as u32;
+    let mut game = HeadlessGame::new(GameMode::Seeded(seed));
+
+    game.app_mut()
+        .insert_resource(tuning)
+        .insert_resource(BotDriver::default());
+    game.start_level_n(level_n);
+    game.clear_events();
+
+    let mut outcome = LevelCheckOutcome::Unsolved;
+
+    for tick in 0..max_level_ticks {
+        game.tick(1);
+
+        if game
+            .events()
+            .iter()
+            .any(|event| matches!(event, GameEvent::GoalReached(_)))
+        {
+            outcome = LevelCheckOutcome::Solved((tick + 1) as f32 * HEADLESS_TIMESTEP_SECONDS);
+            break;
+        }
+
+        if game.state() != AppState::InGame {
+            break;
+        }
+    }
+
+    LevelCheck {
+        seed,
+        level_n,
+        outcome,
+    }
+}
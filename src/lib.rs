@@ -0,0 +1,24 @@
+pub mod app;
+pub mod attract_mode;
+pub mod bot;
+pub mod cli;
+pub mod config;
+pub mod daily_challenge;
+pub mod devtools;
+pub mod environment;
+pub mod game;
+pub mod game_over;
+pub mod ghost;
+pub mod headless;
+pub mod level_check;
+pub mod main_menu;
+pub mod particles;
+pub mod pool;
+pub mod profiling;
+pub mod ring;
+pub mod simulation;
+pub mod sounds;
+pub mod stats;
+pub mod time_trial;
+pub mod tuning;
+pub mod versus;
@@ -1,42 +1,91 @@
-mod app;
-mod devtools;
-mod game;
-mod game_over;
-mod main_menu;
-mod simulation;
-mod sounds;
-
+#[cfg(target_arch = "wasm32")]
+use bevy::asset::AssetMetaCheck;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::{
+    ecs::event::Events,
+    window::{WindowResized, WindowResolution},
+};
 use bevy::{
     input::{keyboard::KeyboardInput, ButtonState},
     prelude::*,
     render::camera::ScalingMode,
     window::{PrimaryWindow, WindowMode},
 };
-#[cfg(not(target_arch = "wasm32"))]
-use bevy::{
-    ecs::event::Events,
-    window::{WindowResized, WindowResolution},
-};
-#[cfg(target_arch = "wasm32")]
-use bevy::asset::AssetMetaCheck;
 use bevy_rapier2d::plugin::{NoUserData, RapierConfiguration, RapierPhysicsPlugin};
+use std::{env, path::Path, process};
 
-use app::{AppState, ButtonPress, UiConfig, VetovoimaColor, PIXELS_PER_METER};
 #[cfg(not(target_arch = "wasm32"))]
-use app::{get_config_or_default, APP_NAME};
-use devtools::DevTools;
-use game::GamePlugin;
-use game_over::GameOverPlugin;
-use main_menu::MainMenuPlugin;
-use simulation::SimulationPlugin;
-use sounds::SoundsPlugin;
+use vetovoima::app::APP_NAME;
+use vetovoima::{
+    app::{
+        AppState, ButtonPress, GameMode, PlayerTwoButtonPress, UiConfig, VetovoimaColor,
+        PIXELS_PER_METER,
+    },
+    attract_mode::AttractModePlugin,
+    bot::{BotDriver, BotPlugin},
+    cli::{parse_args, CliOptions, USAGE},
+    config::{find_config_path, load_config, ConfigPath, ControlLayout, SavedConfig, VVConfig},
+    daily_challenge::DailyChallengePlugin,
+    devtools::{DevConsole, DevTools},
+    game::{GameEvent, GamePlugin, LevelJump},
+    game_over::GameOverPlugin,
+    ghost::GhostPlugin,
+    headless::HeadlessGame,
+    main_menu::{MainMenuPlugin, MenuEvent},
+    simulation::SimulationPlugin,
+    sounds::SoundsPlugin,
+    stats::StatsPlugin,
+    time_trial::TimeTrialPlugin,
+    tuning::TuningPlugin,
+    versus::VersusPlugin,
+};
+
+// Up, down, left, right
+const DIRECTION_KEYS_ARROWS: [KeyCode; 4] = [
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+];
+const DIRECTION_KEYS_WASD: [KeyCode; 4] =
+    [KeyCode::KeyW, KeyCode::KeyS, KeyCode::KeyA, KeyCode::KeyD];
+// About half an hour of game time, in case the bot never runs out of time
+const MAX_HEADLESS_TICKS: u32 = 60 * 60 * 30;
+
+// A game that is started as soon as the menu is shown, given on the command line
+#[derive(Resource)]
+struct StartGameOnLaunch(GameMode);
 
 fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    };
+
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    if options.headless {
+        run_headless(&options);
+        return;
+    }
+
     let mut app = App::new();
+    let is_config_path_given = options.config_path.is_some();
+    let config_path = find_config_path(options.config_path.clone());
+    let saved_config = read_config(&config_path, is_config_path_given);
+    let mut vv_config = saved_config.clone();
+
+    options.apply_to_config(&mut vv_config);
 
     #[cfg(not(target_arch = "wasm32"))]
     {
-        let vv_config = get_config_or_default();
         let window_resolution = match (
             vv_config.window_width_pixels,
             vv_config.window_height_pixels,
@@ -88,29 +137,138 @@ fn main() {
         MainMenuPlugin,
         SimulationPlugin,
         GamePlugin,
+        BotPlugin,
+        AttractModePlugin,
         GameOverPlugin,
-        DevTools,
+        TimeTrialPlugin,
+        GhostPlugin {
+            replay_path: options.replay_path.clone(),
+        },
+        DailyChallengePlugin,
+        VersusPlugin,
+        StatsPlugin,
+        TuningPlugin,
+        options
+            .dev_tools
+            .map(|enabled| DevTools { enabled })
+            .unwrap_or_else(DevTools::from_env),
     ))
     .insert_resource(ClearColor(VetovoimaColor::BLACKISH))
     .insert_resource(ButtonPress::default())
-    .insert_resource(UiConfig::default())
+    .insert_resource(PlayerTwoButtonPress::default())
+    .insert_resource(UiConfig::default().with_user_scale(vv_config.ui_scale))
+    .insert_resource(vv_config)
+    .insert_resource(SavedConfig(saved_config))
+    .insert_resource(ConfigPath(config_path))
+    .insert_resource(GameMode::default())
     .init_state::<AppState>()
     .add_systems(OnEnter(AppState::Init), app_setup)
+    .add_systems(
+        OnEnter(AppState::InMenu),
+        start_game_on_launch.run_if(resource_exists::<StartGameOnLaunch>),
+    )
     .add_systems(
         Update,
         (
-            app_controls,
-            keyboard_input,
+            // The dev console takes all the keyboard input while it's open
+            (app_controls, keyboard_input).run_if(not(resource_exists::<DevConsole>)),
             transition_to_in_menu.run_if(in_state(AppState::Init)),
+            // Settings are applied right away when they change
+            apply_ui_scale.run_if(resource_changed::<VVConfig>),
         ),
     );
 
     #[cfg(not(target_arch = "wasm32"))]
-    app.add_systems(Update, window_resize);
+    app.add_systems(
+        Update,
+        (
+            window_resize,
+            apply_window_config.run_if(resource_changed::<VVConfig>),
+        ),
+    );
+
+    if let Some(game_mode) = options.game_mode() {
+        app.insert_resource(StartGameOnLaunch(game_mode));
+    }
+
+    if let Some(level_n) = options.level {
+        app.insert_resource(LevelJump(level_n));
+    }
 
     app.run();
 }
 
+// The bot plays until the game is over, the results are printed instead of shown
+fn run_headless(options: &CliOptions) {
+    let game_mode = options.game_mode().unwrap_or(GameMode::Endless);
+    let mut game = HeadlessGame::new(game_mode);
+
+    game.app_mut().insert_resource(BotDriver::default());
+    game.start_level_n(options.level.unwrap_or(1));
+    game.clear_events();
+
+    println!("Playing {:?} headless", game_mode);
+
+    for _ in 0..MAX_HEADLESS_TICKS {
+        game.tick(1);
+
+        for event in game.events() {
+            if let GameEvent::GoalReached(_) = event {
+                if let Some(game_level) = game.level() {
+                    println!(
+                        "Level {} (seed {:016x}) completed",
+                        game_level.n, game_level.seed
+                    );
+                }
+            }
+        }
+
+        game.clear_events();
+
+        if game.state() == AppState::GameOver {
+            break;
+        }
+    }
+
+    match game.level() {
+        Some(game_level) => println!("Game over at level {}", game_level.n),
+        None => println!("Game over"),
+    }
+}
+
+fn start_game_on_launch(
+    mut commands: Commands,
+    start_game: Res<StartGameOnLaunch>,
+    mut menu_event: EventWriter<MenuEvent>,
+) {
+    menu_event.send(MenuEvent::BeginNewGame(start_game.0));
+    commands.remove_resource::<StartGameOnLaunch>();
+}
+
+fn read_config(config_path: &Path, is_config_path_given: bool) -> VVConfig {
+    match load_config(config_path) {
+        Ok(Some(config)) => config,
+        // Nothing has been saved yet
+        Ok(None) => VVConfig::default(),
+        Err(errors) => {
+            eprintln!("Invalid config {}", config_path.display());
+
+            for error in errors {
+                eprintln!("  {}", error);
+            }
+
+            // The config that was asked for must be valid, otherwise the defaults will do
+            if is_config_path_given {
+                process::exit(1);
+            }
+
+            eprintln!("Falling back to default config");
+
+            VVConfig::default()
+        }
+    }
+}
+
 fn app_setup(mut commands: Commands, primary_window: Query<&Window, With<PrimaryWindow>>) {
     let Ok(window) = primary_window.get_single() else {
         return;
@@ -161,29 +319,58 @@ fn transition_to_in_menu(mut app_state: ResMut<NextState<AppState>>) {
 fn keyboard_input(
     mut keyboard_events: EventReader<KeyboardInput>,
     mut button_press: ResMut<ButtonPress>,
+    mut player_two_button_press: ResMut<PlayerTwoButtonPress>,
+    game_mode: Res<GameMode>,
+    vv_config: Res<VVConfig>,
 ) {
+    let is_versus = *game_mode == GameMode::Versus;
+    // The second player gets the keys that the first player doesn't use
+    let (player_one_keys, player_two_keys) = match vv_config.controls {
+        ControlLayout::Arrows => (DIRECTION_KEYS_ARROWS, DIRECTION_KEYS_WASD),
+        ControlLayout::Wasd => (DIRECTION_KEYS_WASD, DIRECTION_KEYS_ARROWS),
+    };
+
     for event in keyboard_events.read() {
         let is_pressed = ButtonState::is_pressed(&event.state);
 
         match event.key_code {
-            KeyCode::ArrowUp => button_press.up_pressed = is_pressed,
-            KeyCode::ArrowDown => button_press.down_pressed = is_pressed,
-            KeyCode::ArrowLeft => button_press.left_pressed = is_pressed,
-            KeyCode::ArrowRight => button_press.right_pressed = is_pressed,
             KeyCode::Enter => button_press.main_control_pressed = is_pressed,
             KeyCode::Escape => button_press.select_pressed = is_pressed,
 
-            _ => (),
+            key_code => {
+                if let Some(direction) = player_one_keys.iter().position(|key| *key == key_code) {
+                    press_direction(&mut button_press, direction, is_pressed);
+                } else if let Some(direction) =
+                    player_two_keys.iter().position(|key| *key == key_code)
+                {
+                    if is_versus {
+                        press_direction(&mut player_two_button_press.0, direction, is_pressed);
+                    }
+                }
+            }
         }
     }
 }
 
+// The direction is an index to DIRECTION_KEYS_ARROWS / DIRECTION_KEYS_WASD
+fn press_direction(button_press: &mut ButtonPress, direction: usize, is_pressed: bool) {
+    match direction {
+        0 => button_press.up_pressed = is_pressed,
+        1 => button_press.down_pressed = is_pressed,
+        2 => button_press.left_pressed = is_pressed,
+        3 => button_press.right_pressed = is_pressed,
+
+        _ => (),
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn window_resize(
     resize_event: Res<Events<WindowResized>>,
     primary_window: Query<&Window, With<PrimaryWindow>>,
     mut query: Query<&mut OrthographicProjection, With<Camera2d>>,
     mut ui_config: ResMut<UiConfig>,
+    vv_config: Res<VVConfig>,
 ) {
     let mut reader = resize_event.get_cursor();
     for event in reader.read(&resize_event) {
@@ -195,7 +382,7 @@ fn window_resize(
                 window_to_projection_scale(window, Some(event.height));
             // The world created at 4k, then scaled to fit the practical resolution
             let scale_ratio = 2160.0 / window_height;
-            let scaled_ui_config = UiConfig::scale(scale_ratio);
+            let scaled_ui_config = UiConfig::scale(scale_ratio).with_user_scale(vv_config.ui_scale);
 
             projection.scale = projection_scale;
             // the multiplier leaves some margin around the visuals
@@ -207,6 +394,55 @@ fn window_resize(
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn apply_window_config(
+    mut primary_window: Query<&mut Window, With<PrimaryWindow>>,
+    vv_config: Res<VVConfig>,
+) {
+    let Ok(mut window) = primary_window.get_single_mut() else {
+        return;
+    };
+
+    if window.mode != vv_config.window_mode {
+        window.mode = vv_config.window_mode;
+    }
+
+    if let (Some(width), Some(height)) = (
+        vv_config.window_width_pixels,
+        vv_config.window_height_pixels,
+    ) {
+        if window.resolution.width() != width as f32 || window.resolution.height() != height as f32
+        {
+            window.resolution.set(width as f32, height as f32);
+        }
+    }
+}
+
+fn apply_ui_scale(
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    mut ui_config: ResMut<UiConfig>,
+    vv_config: Res<VVConfig>,
+) {
+    let Ok(window) = primary_window.get_single() else {
+        return;
+    };
+
+    *ui_config = window_ui_config(window).with_user_scale(vv_config.ui_scale);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn window_ui_config(window: &Window) -> UiConfig {
+    let (_, window_height) = window_to_projection_scale(window, None);
+
+    UiConfig::scale(2160.0 / window_height)
+}
+
+// The web version has a fixed size canvas
+#[cfg(target_arch = "wasm32")]
+fn window_ui_config(_window: &Window) -> UiConfig {
+    UiConfig::default()
+}
+
 fn window_to_projection_scale(window: &Window, height_override: Option<f32>) -> (f32, f32) {
     let height = if window.mode == WindowMode::Windowed {
         height_override.unwrap_or_else(|| window.height())
@@ -1,7 +1,9 @@
-use bevy::{app::AppExit, prelude::*};
+use bevy::{app::AppExit, prelude::*, window::WindowMode};
 
 use crate::{
-    app::{cursor_visible, AppState, ButtonPress, UiConfig, VetovoimaColor, APP_NAME},
+    app::{cursor_visible, AppState, ButtonPress, GameMode, UiConfig, VetovoimaColor, APP_NAME},
+    config::{save_config, ConfigPath, ControlLayout, SavedConfig, VVConfig},
+    daily_challenge::ChallengeDate,
     game::GameLevel,
 };
 
@@ -9,26 +11,250 @@ const BUTTON_COLOR: Color = VetovoimaColor::BLUEISH_DARK;
 const BUTTON_COLOR_HOVER: Color = VetovoimaColor::BLUEISH_MID;
 const BUTTON_ACTIVE_COLOR: Color = VetovoimaColor::BLUEISH_LIGHT;
 static NEW_GAME_BUTTON_LABEL: &str = "New game";
+static TIME_TRIAL_BUTTON_LABEL: &str = "Time trial";
+static DAILY_CHALLENGE_BUTTON_LABEL: &str = "Daily";
+static VERSUS_BUTTON_LABEL: &str = "Versus";
+static SETTINGS_BUTTON_LABEL: &str = "Settings";
 static EXIT_BUTTON_LABEL: &str = "Exit";
 
 #[derive(Event)]
 pub enum MenuEvent {
     EnterMenu,
-    BeginNewGame,
+    BeginNewGame(GameMode),
 }
 
 #[derive(Component, PartialEq, Clone, Copy, Debug)]
 enum MenuButton {
     NewGame,
+    TimeTrial,
+    DailyChallenge,
+    Versus,
+    Settings,
+    // There's nothing to exit to on the web
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
     Exit,
 }
 
+// The order of buttons in the menu (top to bottom)
+#[cfg(not(target_arch = "wasm32"))]
+const MENU_BUTTONS: [MenuButton; 6] = [
+    MenuButton::NewGame,
+    MenuButton::TimeTrial,
+    MenuButton::DailyChallenge,
+    MenuButton::Versus,
+    MenuButton::Settings,
+    MenuButton::Exit,
+];
+#[cfg(target_arch = "wasm32")]
+const MENU_BUTTONS: [MenuButton; 5] = [
+    MenuButton::NewGame,
+    MenuButton::TimeTrial,
+    MenuButton::DailyChallenge,
+    MenuButton::Versus,
+    MenuButton::Settings,
+];
+
+impl MenuButton {
+    fn label(&self) -> &'static str {
+        match self {
+            MenuButton::NewGame => NEW_GAME_BUTTON_LABEL,
+            MenuButton::TimeTrial => TIME_TRIAL_BUTTON_LABEL,
+            MenuButton::DailyChallenge => DAILY_CHALLENGE_BUTTON_LABEL,
+            MenuButton::Versus => VERSUS_BUTTON_LABEL,
+            MenuButton::Settings => SETTINGS_BUTTON_LABEL,
+            MenuButton::Exit => EXIT_BUTTON_LABEL,
+        }
+    }
+}
+
+#[derive(Component, PartialEq, Clone, Copy, Debug)]
+enum SettingsItem {
+    // The window can't be changed on the web
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    WindowMode,
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    Resolution,
+    MainVolume,
+    EffectsVolume,
+    UiScale,
+    ColorblindPalette,
+    Controls,
+    Back,
+}
+
+// The order of items in the settings (top to bottom)
+#[cfg(not(target_arch = "wasm32"))]
+const SETTINGS_ITEMS: [SettingsItem; 8] = [
+    SettingsItem::WindowMode,
+    SettingsItem::Resolution,
+    SettingsItem::MainVolume,
+    SettingsItem::EffectsVolume,
+    SettingsItem::UiScale,
+    SettingsItem::ColorblindPalette,
+    SettingsItem::Controls,
+    SettingsItem::Back,
+];
+#[cfg(target_arch = "wasm32")]
+const SETTINGS_ITEMS: [SettingsItem; 6] = [
+    SettingsItem::MainVolume,
+    SettingsItem::EffectsVolume,
+    SettingsItem::UiScale,
+    SettingsItem::ColorblindPalette,
+    SettingsItem::Controls,
+    SettingsItem::Back,
+];
+
+const WINDOW_MODES: [WindowMode; 3] = [
+    WindowMode::Windowed,
+    WindowMode::BorderlessFullscreen(MonitorSelection::Primary),
+    WindowMode::Fullscreen(MonitorSelection::Primary),
+];
+// Width and height (pixels), only used in windowed mode
+const RESOLUTIONS: [(u32, u32); 4] = [(720, 720), (1280, 720), (1920, 1080), (2560, 1440)];
+const UI_SCALES: [f32; 5] = [0.75, 1.0, 1.25, 1.5, 2.0];
+const VOLUME_STEP: f64 = 0.1;
+
+impl SettingsItem {
+    fn label(&self, vv_config: &VVConfig) -> String {
+        match self {
+            SettingsItem::WindowMode => {
+                let window_mode = match vv_config.window_mode {
+                    WindowMode::Windowed => "Windowed",
+                    WindowMode::BorderlessFullscreen(_) => "Borderless",
+                    _ => "Fullscreen",
+                };
+
+                format!("Window: {}", window_mode)
+            }
+            SettingsItem::Resolution => match (
+                vv_config.window_width_pixels,
+                vv_config.window_height_pixels,
+            ) {
+                (Some(width), Some(height)) => format!("Resolution: {}x{}", width, height),
+                _ => "Resolution: auto".to_string(),
+            },
+            SettingsItem::MainVolume => {
+                format!("Volume: {:.0}%", vv_config.main_volume * 100.0)
+            }
+            SettingsItem::EffectsVolume => {
+                format!("Effects: {:.0}%", vv_config.effects_volume * 100.0)
+            }
+            SettingsItem::UiScale => format!("UI scale: {:.0}%", vv_config.ui_scale * 100.0),
+            SettingsItem::ColorblindPalette => {
+                let toggle = if vv_config.colorblind_palette {
+                    "on"
+                } else {
+                    "off"
+                };
+
+                format!("Colorblind: {}", toggle)
+            }
+            SettingsItem::Controls => match vv_config.controls {
+                ControlLayout::Arrows => "Controls: arrows".to_string(),
+                ControlLayout::Wasd => "Controls: WASD".to_string(),
+            },
+            SettingsItem::Back => "Back".to_string(),
+        }
+    }
+
+    // Steps through the options of the setting (backwards with a negative step)
+    fn change(&self, vv_config: &mut VVConfig, step: isize) {
+        match self {
+            SettingsItem::WindowMode => {
+                vv_config.window_mode = cycle(&WINDOW_MODES, vv_config.window_mode, step);
+            }
+            SettingsItem::Resolution => {
+                let current = vv_config
+                    .window_width_pixels
+                    .zip(vv_config.window_height_pixels)
+                    .unwrap_or(RESOLUTIONS[0]);
+                let (width, height) = cycle(&RESOLUTIONS, current, step);
+
+                vv_config.window_width_pixels = Some(width);
+                vv_config.window_height_pixels = Some(height);
+            }
+            SettingsItem::MainVolume => {
+                vv_config.main_volume = step_volume(vv_config.main_volume, step);
+            }
+            SettingsItem::EffectsVolume => {
+                vv_config.effects_volume = step_volume(vv_config.effects_volume, step);
+            }
+            SettingsItem::UiScale => {
+                vv_config.ui_scale = cycle(&UI_SCALES, vv_config.ui_scale, step);
+            }
+            SettingsItem::ColorblindPalette => {
+                vv_config.colorblind_palette = !vv_config.colorblind_palette;
+            }
+            SettingsItem::Controls => {
+                vv_config.controls = match vv_config.controls {
+                    ControlLayout::Arrows => ControlLayout::Wasd,
+                    ControlLayout::Wasd => ControlLayout::Arrows,
+                };
+            }
+            SettingsItem::Back => (),
+        }
+    }
+
+    // Only the changed setting is saved, the rest of the config in use may be overridden
+    // from the command line
+    fn save(&self, vv_config: &VVConfig, saved_config: &mut SavedConfig, config_path: &ConfigPath) {
+        let saved = &mut saved_config.0;
+
+        match self {
+            SettingsItem::WindowMode => saved.window_mode = vv_config.window_mode,
+            SettingsItem::Resolution => {
+                saved.window_width_pixels = vv_config.window_width_pixels;
+                saved.window_height_pixels = vv_config.window_height_pixels;
+            }
+            SettingsItem::MainVolume => saved.main_volume = vv_config.main_volume,
+            SettingsItem::EffectsVolume => saved.effects_volume = vv_config.effects_volume,
+            SettingsItem::UiScale => saved.ui_scale = vv_config.ui_scale,
+            SettingsItem::ColorblindPalette => {
+                saved.colorblind_palette = vv_config.colorblind_palette;
+            }
+            SettingsItem::Controls => saved.controls = vv_config.controls,
+            SettingsItem::Back => return,
+        }
+
+        save_config(&config_path.0, saved);
+    }
+}
+
+// An unknown value (e.g. set by hand in the config file) starts over from the first option
+fn cycle<T: PartialEq + Copy>(options: &[T], current: T, step: isize) -> T {
+    let next_index = match options.iter().position(|option| *option == current) {
+        Some(index) => (index as isize + step).rem_euclid(options.len() as isize) as usize,
+        None => 0,
+    };
+
+    options[next_index]
+}
+
+fn step_volume(volume: f64, step: isize) -> f64 {
+    let next_volume = (volume + step as f64 * VOLUME_STEP).clamp(0.0, 1.0);
+
+    // Avoid drifting away from the even steps
+    (next_volume / VOLUME_STEP).round() * VOLUME_STEP
+}
+
 #[derive(Component)]
 struct MainMenu;
 
 #[derive(Component, Resource)]
 struct SelectedButton(Option<MenuButton>);
 
+// Present while the settings are shown in place of the main menu
+#[derive(Resource)]
+struct SettingsScreen {
+    selected_item: usize,
+}
+
+#[derive(Component)]
+struct SettingsMenu;
+
+#[derive(Component)]
+struct SettingsItemText(SettingsItem);
+
 pub struct MainMenuPlugin;
 
 impl Plugin for MainMenuPlugin {
@@ -42,12 +268,30 @@ impl Plugin for MainMenuPlugin {
             .add_systems(
                 Update,
                 (
-                    mouse_interaction,
-                    selected_button_change,
-                    button_press,
+                    (mouse_interaction, selected_button_change, button_press)
+                        .run_if(not(resource_exists::<SettingsScreen>)),
                     init_game,
                 ),
             )
+            .add_systems(
+                Update,
+                (
+                    settings_mouse_interaction,
+                    settings_button_press,
+                    settings_menu_update,
+                )
+                    .chain()
+                    .run_if(resource_exists::<SettingsScreen>),
+            )
+            .add_systems(
+                Update,
+                (
+                    show_settings.run_if(resource_added::<SettingsScreen>),
+                    hide_settings
+                        .run_if(resource_removed::<SettingsScreen>)
+                        .run_if(in_state(AppState::InMenu)),
+                ),
+            )
             .add_systems(OnExit(AppState::InMenu), hide_menu);
     }
 }
@@ -60,13 +304,32 @@ fn show_menu(
     ui_config: Res<UiConfig>,
 ) {
     let font = asset_server.load(ui_config.font_filename);
-    let button_width = 400.0 * ui_config.scale_multiplier;
-    let button_height = 80.0 * ui_config.scale_multiplier;
-    let margin = 10.0 * ui_config.scale_multiplier;
 
     selected_button.0 = None;
     menu_event.send(MenuEvent::EnterMenu);
 
+    let menu = spawn_menu(
+        &mut commands,
+        font,
+        &ui_config,
+        VetovoimaColor::BLACKISH,
+        true,
+    );
+    commands.entity(menu).insert(MainMenu);
+}
+
+// A non-interactive menu only shows how the menu looks (e.g. on top of a demo)
+pub fn spawn_menu(
+    commands: &mut Commands,
+    font: Handle<Font>,
+    ui_config: &UiConfig,
+    background_color: Color,
+    interactive: bool,
+) -> Entity {
+    let button_width = 400.0 * ui_config.scale_multiplier;
+    let button_height = 80.0 * ui_config.scale_multiplier;
+    let margin = 10.0 * ui_config.scale_multiplier;
+
     commands
         .spawn((
             Node {
@@ -78,9 +341,8 @@ fn show_menu(
                 padding: UiRect::all(Val::Px(10.0)),
                 ..Default::default()
             },
-            BackgroundColor(VetovoimaColor::BLACKISH.into()),
+            BackgroundColor(background_color),
         ))
-        .insert(MainMenu)
         .with_children(|menu_node| {
             menu_node
                 .spawn((
@@ -92,7 +354,7 @@ fn show_menu(
                         margin: UiRect::all(Val::Px(margin * 2.0)),
                         ..default()
                     },
-                    BackgroundColor(VetovoimaColor::BLACKISH.into()),
+                    BackgroundColor(Color::NONE),
                 ))
                 .with_children(|parent| {
                     parent.spawn((
@@ -106,9 +368,8 @@ fn show_menu(
                     ));
                 });
 
-            menu_node
-                .spawn((
-                    Button,
+            for button in MENU_BUTTONS {
+                let mut button_node = menu_node.spawn((
                     Node {
                         width: Val::Px(button_width),
                         height: Val::Px(button_height),
@@ -118,37 +379,11 @@ fn show_menu(
                         ..default()
                     },
                     BackgroundColor(BUTTON_COLOR),
-                ))
-                .with_children(|parent| {
-                    parent.spawn((
-                        Text::new(NEW_GAME_BUTTON_LABEL),
-                        TextFont {
-                            font: font.clone(),
-                            font_size: ui_config.font_size_menu_item,
-                            ..Default::default()
-                        },
-                        TextColor(VetovoimaColor::WHITEISH),
-                    ));
-                })
-                .insert(MenuButton::NewGame);
+                ));
 
-            #[cfg(not(target_arch = "wasm32"))]
-            menu_node
-                .spawn((
-                    Button,
-                    Node {
-                        width: Val::Px(button_width),
-                        height: Val::Px(button_height),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        margin: UiRect::all(Val::Px(margin)),
-                        ..default()
-                    },
-                    BackgroundColor(BUTTON_COLOR),
-                ))
-                .with_children(|parent| {
+                button_node.with_children(|parent| {
                     parent.spawn((
-                        Text::new(EXIT_BUTTON_LABEL),
+                        Text::new(button.label()),
                         TextFont {
                             font: font.clone(),
                             font_size: ui_config.font_size_menu_item,
@@ -156,17 +391,31 @@ fn show_menu(
                         },
                         TextColor(VetovoimaColor::WHITEISH),
                     ));
-                })
-                .insert(MenuButton::Exit);
-        });
+                });
+
+                if interactive {
+                    button_node.insert((Button, button));
+                }
+            }
+        })
+        .id()
 }
 
-fn hide_menu(mut commands: Commands, menu: Query<Entity, With<MainMenu>>) {
-    let menu = menu.get_single().expect("Could not hide the menu");
-    commands.entity(menu).despawn_recursive();
+fn hide_menu(
+    mut commands: Commands,
+    menu_query: Query<Entity, With<MainMenu>>,
+    settings_query: Query<Entity, With<SettingsMenu>>,
+) {
+    // The menu may be left while the settings are open (e.g. for the demo)
+    commands.remove_resource::<SettingsScreen>();
+
+    for menu in menu_query.iter().chain(settings_query.iter()) {
+        commands.entity(menu).despawn_recursive();
+    }
 }
 
 fn mouse_interaction(
+    mut commands: Commands,
     mut interaction_query: Query<
         (&Interaction, &MenuButton, &mut BackgroundColor),
         (Changed<Interaction>, With<MenuButton>),
@@ -182,7 +431,21 @@ fn mouse_interaction(
 
                 match button {
                     MenuButton::NewGame => {
-                        menu_event.send(MenuEvent::BeginNewGame);
+                        menu_event.send(MenuEvent::BeginNewGame(GameMode::Endless));
+                    }
+                    MenuButton::TimeTrial => {
+                        menu_event.send(MenuEvent::BeginNewGame(GameMode::TimeTrial));
+                    }
+                    MenuButton::DailyChallenge => {
+                        menu_event.send(MenuEvent::BeginNewGame(GameMode::DailyChallenge(
+                            ChallengeDate::today(),
+                        )));
+                    }
+                    MenuButton::Versus => {
+                        menu_event.send(MenuEvent::BeginNewGame(GameMode::Versus));
+                    }
+                    MenuButton::Settings => {
+                        commands.insert_resource(SettingsScreen { selected_item: 0 });
                     }
                     MenuButton::Exit => {
                         #[cfg(not(target_arch = "wasm32"))]
@@ -220,6 +483,7 @@ fn selected_button_change(
 }
 
 fn button_press(
+    mut commands: Commands,
     button_press: Res<ButtonPress>,
     mut selected_button: ResMut<SelectedButton>,
     mut menu_event: EventWriter<MenuEvent>,
@@ -232,7 +496,21 @@ fn button_press(
     if button_press.main_control_pressed {
         match selected_button.0 {
             Some(MenuButton::NewGame) => {
-                menu_event.send(MenuEvent::BeginNewGame);
+                menu_event.send(MenuEvent::BeginNewGame(GameMode::Endless));
+            }
+            Some(MenuButton::TimeTrial) => {
+                menu_event.send(MenuEvent::BeginNewGame(GameMode::TimeTrial));
+            }
+            Some(MenuButton::DailyChallenge) => {
+                menu_event.send(MenuEvent::BeginNewGame(GameMode::DailyChallenge(
+                    ChallengeDate::today(),
+                )));
+            }
+            Some(MenuButton::Versus) => {
+                menu_event.send(MenuEvent::BeginNewGame(GameMode::Versus));
+            }
+            Some(MenuButton::Settings) => {
+                commands.insert_resource(SettingsScreen { selected_item: 0 });
             }
             Some(MenuButton::Exit) => {
                 #[cfg(not(target_arch = "wasm32"))]
@@ -241,22 +519,219 @@ fn button_press(
 
             _ => (),
         }
-    } else if button_press.up_pressed || button_press.down_pressed {
-        selected_button.0 = Some(select_next_button(selected_button.0));
+    } else if button_press.up_pressed {
+        selected_button.0 = Some(select_next_button(selected_button.0, -1));
+    } else if button_press.down_pressed {
+        selected_button.0 = Some(select_next_button(selected_button.0, 1));
+    }
+}
+
+fn select_next_button(selected_button: Option<MenuButton>, step: isize) -> MenuButton {
+    let buttons_amount = MENU_BUTTONS.len() as isize;
+
+    match selected_button.and_then(|button| MENU_BUTTONS.iter().position(|b| *b == button)) {
+        Some(index) => {
+            let next_index = (index as isize + step).rem_euclid(buttons_amount);
+            MENU_BUTTONS[next_index as usize]
+        }
+
+        None => MENU_BUTTONS[0],
+    }
+}
+
+fn show_settings(
+    mut commands: Commands,
+    menu_query: Query<Entity, With<MainMenu>>,
+    vv_config: Res<VVConfig>,
+    asset_server: Res<AssetServer>,
+    ui_config: Res<UiConfig>,
+) {
+    let font = asset_server.load(ui_config.font_filename);
+    let margin = 10.0 * ui_config.scale_multiplier;
+
+    for menu in menu_query.iter() {
+        commands.entity(menu).despawn_recursive();
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(10.0)),
+                ..Default::default()
+            },
+            BackgroundColor(VetovoimaColor::BLACKISH),
+            SettingsMenu,
+        ))
+        .with_children(|menu_node| {
+            menu_node.spawn((
+                Text::new(SETTINGS_BUTTON_LABEL),
+                TextFont {
+                    font: font.clone(),
+                    font_size: ui_config.font_size_menu_item,
+                    ..Default::default()
+                },
+                TextColor(VetovoimaColor::WHITEISH),
+                Node {
+                    margin: UiRect::all(Val::Px(margin * 2.0)),
+                    ..default()
+                },
+            ));
+
+            // The sizes and colors are set in settings_menu_update
+            for item in SETTINGS_ITEMS {
+                menu_node
+                    .spawn((
+                        Button,
+                        item,
+                        Node {
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(BUTTON_COLOR),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text::new(item.label(&vv_config)),
+                            TextFont {
+                                font: font.clone(),
+                                ..Default::default()
+                            },
+                            TextColor(VetovoimaColor::WHITEISH),
+                            SettingsItemText(item),
+                        ));
+                    });
+            }
+        });
+}
+
+fn hide_settings(
+    mut commands: Commands,
+    mut selected_button: ResMut<SelectedButton>,
+    settings_query: Query<Entity, With<SettingsMenu>>,
+    asset_server: Res<AssetServer>,
+    ui_config: Res<UiConfig>,
+) {
+    let font = asset_server.load(ui_config.font_filename);
+
+    for settings_menu in settings_query.iter() {
+        commands.entity(settings_menu).despawn_recursive();
     }
+
+    let menu = spawn_menu(
+        &mut commands,
+        font,
+        &ui_config,
+        VetovoimaColor::BLACKISH,
+        true,
+    );
+    commands.entity(menu).insert(MainMenu);
+    // Highlight the selected button again
+    selected_button.set_changed();
 }
 
-fn select_next_button(selected_button: Option<MenuButton>) -> MenuButton {
-    match selected_button {
-        Some(button) => {
-            if button == MenuButton::NewGame {
-                MenuButton::Exit
-            } else {
-                MenuButton::NewGame
+fn settings_mouse_interaction(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &SettingsItem), Changed<Interaction>>,
+    mut settings_screen: ResMut<SettingsScreen>,
+    mut vv_config: ResMut<VVConfig>,
+    mut saved_config: ResMut<SavedConfig>,
+    config_path: Res<ConfigPath>,
+) {
+    for (interaction, item) in interaction_query.iter() {
+        let Some(item_index) = SETTINGS_ITEMS.iter().position(|i| i == item) else {
+            continue;
+        };
+
+        match *interaction {
+            Interaction::Pressed => {
+                if *item == SettingsItem::Back {
+                    commands.remove_resource::<SettingsScreen>();
+                } else {
+                    item.change(&mut vv_config, 1);
+                    item.save(&vv_config, &mut saved_config, &config_path);
+                }
+            }
+            Interaction::Hovered => {
+                settings_screen.selected_item = item_index;
             }
+            Interaction::None => (),
         }
+    }
+}
+
+fn settings_button_press(
+    mut commands: Commands,
+    button_press: Res<ButtonPress>,
+    mut settings_screen: ResMut<SettingsScreen>,
+    mut vv_config: ResMut<VVConfig>,
+    mut saved_config: ResMut<SavedConfig>,
+    config_path: Res<ConfigPath>,
+) {
+    // The same press that opened the settings shouldn't change anything
+    if !button_press.is_changed() || settings_screen.is_added() {
+        return;
+    }
+
+    let items_amount = SETTINGS_ITEMS.len() as isize;
+    let selected_item = SETTINGS_ITEMS[settings_screen.selected_item];
+    let step = if button_press.main_control_pressed || button_press.right_pressed {
+        1
+    } else if button_press.left_pressed {
+        -1
+    } else {
+        0
+    };
+
+    if button_press.select_pressed
+        || (button_press.main_control_pressed && selected_item == SettingsItem::Back)
+    {
+        commands.remove_resource::<SettingsScreen>();
+    } else if step != 0 && selected_item != SettingsItem::Back {
+        selected_item.change(&mut vv_config, step);
+        selected_item.save(&vv_config, &mut saved_config, &config_path);
+    } else if button_press.up_pressed {
+        settings_screen.selected_item =
+            (settings_screen.selected_item as isize - 1).rem_euclid(items_amount) as usize;
+    } else if button_press.down_pressed {
+        settings_screen.selected_item =
+            (settings_screen.selected_item as isize + 1).rem_euclid(items_amount) as usize;
+    }
+}
+
+fn settings_menu_update(
+    mut item_query: Query<(&SettingsItem, &mut Node, &mut BackgroundColor)>,
+    mut text_query: Query<(&SettingsItemText, &mut Text, &mut TextFont)>,
+    settings_screen: Res<SettingsScreen>,
+    vv_config: Res<VVConfig>,
+    ui_config: Res<UiConfig>,
+) {
+    if !settings_screen.is_changed() && !vv_config.is_changed() && !ui_config.is_changed() {
+        return;
+    }
+
+    let selected_item = SETTINGS_ITEMS[settings_screen.selected_item];
+    let margin = 5.0 * ui_config.scale_multiplier;
+
+    for (item, mut node, mut color) in item_query.iter_mut() {
+        node.width = Val::Px(600.0 * ui_config.scale_multiplier);
+        node.height = Val::Px(56.0 * ui_config.scale_multiplier);
+        node.margin = UiRect::all(Val::Px(margin));
+        *color = if *item == selected_item {
+            BUTTON_ACTIVE_COLOR.into()
+        } else {
+            BUTTON_COLOR.into()
+        };
+    }
 
-        None => MenuButton::NewGame,
+    for (item_text, mut text, mut text_font) in text_query.iter_mut() {
+        **text = item_text.0.label(&vv_config);
+        text_font.font_size = ui_config.font_size_countdown_large;
     }
 }
 
@@ -268,8 +743,9 @@ fn init_game(
     // Effectively resets the game (start from level 1)
     for event in menu_event.read() {
         match event {
-            MenuEvent::BeginNewGame => {
+            MenuEvent::BeginNewGame(game_mode) => {
                 commands.remove_resource::<GameLevel>();
+                commands.insert_resource(*game_mode);
                 app_state.set(AppState::LoadingLevel);
             }
 
@@ -0,0 +1,281 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::Velocity;
+use rand::{thread_rng, Rng};
+use std::f32::consts::TAU;
+use std::ops::RangeInclusive;
+
+use crate::pool::{PoolCommands, Pooled};
+use crate::simulation::{gravity_force_at, GravitySource};
+use crate::tuning::Tuning;
+
+const Z_INDEX_PARTICLES: f32 = 1.5;
+// The gravity is integrated here instead of by Rapier, as if every particle weighed this much
+const PARTICLE_MASS: f32 = 100.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleShape {
+    Circle,
+    Square,
+}
+
+// What the particles of an emitter (or a burst) look like and how they move
+#[derive(Clone, Debug)]
+pub struct ParticleEffect {
+    pub shape: ParticleShape,
+    // The color of a particle fades from the first to the second over its lifetime
+    pub gradient: (Color, Color),
+    // Each particle gets a hue of its own, up to this many degrees off the gradient
+    pub hue_variation: f32,
+    pub lifetime: f32,
+    // Per emission
+    pub amount: RangeInclusive<u32>,
+    pub radius: RangeInclusive<f32>,
+    pub speed: RangeInclusive<f32>,
+    // The particles fly within this angle (radians) of the direction, PI covers every direction
+    pub spread: f32,
+    // How much of the velocity of the emitter the particles keep
+    pub inherited_velocity: f32,
+    // Like the force ratio of `Attractable`, 0.0 for no gravity at all
+    pub gravity_ratio: f32,
+}
+
+// Emits the effect on an interval while active. The direction is in the local space of the
+// entity, and the particles inherit the velocity of the entity (or its parent).
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub effect: ParticleEffect,
+    pub direction: Vec2,
+    pub interval: Timer,
+    pub active: bool,
+}
+
+impl ParticleEmitter {
+    pub fn new(effect: ParticleEffect, direction: Vec2, interval_secs: f32) -> Self {
+        ParticleEmitter {
+            effect,
+            direction,
+            interval: Timer::from_seconds(interval_secs, TimerMode::Repeating),
+            active: false,
+        }
+    }
+}
+
+// A single emission of an effect, e.g. for an impact
+#[derive(Event, Clone, Debug)]
+pub struct ParticleBurst {
+    pub effect: ParticleEffect,
+    pub position: Vec2,
+    pub direction: Vec2,
+    pub velocity: Vec2,
+}
+
+#[derive(Component)]
+pub struct Particle {
+    lifetime: Timer,
+    velocity: Vec2,
+    gradient: (Color, Color),
+    gravity_ratio: f32,
+}
+
+// Every particle of a shape is the same mesh, scaled to its radius
+#[derive(Resource)]
+pub struct ParticleMeshes {
+    circle: Handle<Mesh>,
+    square: Handle<Mesh>,
+}
+
+impl ParticleMeshes {
+    fn get(&self, shape: ParticleShape) -> Handle<Mesh> {
+        match shape {
+            ParticleShape::Circle => self.circle.clone(),
+            ParticleShape::Square => self.square.clone(),
+        }
+    }
+}
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ParticleBurst>()
+            .add_systems(Startup, particles_setup)
+            // The particles live out their lifetime even if the level ends
+            .add_systems(
+                Update,
+                (
+                    emit_particles,
+                    emit_particle_bursts,
+                    add_particle_visuals,
+                    update_particles,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn particles_setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(ParticleMeshes {
+        circle: meshes.add(Circle::new(1.0)),
+        square: meshes.add(Rectangle::from_length(2.0)),
+    });
+}
+
+pub fn emit_particles(
+    mut commands: Commands,
+    mut emitter_query: Query<(
+        Entity,
+        &mut ParticleEmitter,
+        &GlobalTransform,
+        Option<&Parent>,
+    )>,
+    velocity_query: Query<&Velocity>,
+    particle_meshes: Res<ParticleMeshes>,
+    time: Res<Time>,
+) {
+    let mut rng = thread_rng();
+
+    for (entity, mut emitter, global_transform, parent) in emitter_query.iter_mut() {
+        emitter.interval.tick(time.delta());
+
+        if !emitter.active || !emitter.interval.just_finished() {
+            continue;
+        }
+
+        let velocity = velocity_query
+            .get(entity)
+            .ok()
+            .or_else(|| parent.and_then(|parent| velocity_query.get(parent.get()).ok()))
+            .map_or(Vec2::ZERO, |velocity| velocity.linvel);
+        let direction = global_transform.rotation() * emitter.direction.extend(0.0);
+
+        spawn_particles(
+            &mut commands,
+            &particle_meshes,
+            &emitter.effect,
+            global_transform.translation().truncate(),
+            direction.truncate(),
+            velocity,
+            &mut rng,
+        );
+    }
+}
+
+fn emit_particle_bursts(
+    mut commands: Commands,
+    mut particle_bursts: EventReader<ParticleBurst>,
+    particle_meshes: Res<ParticleMeshes>,
+) {
+    let mut rng = thread_rng();
+
+    for burst in particle_bursts.read() {
+        spawn_particles(
+            &mut commands,
+            &particle_meshes,
+            &burst.effect,
+            burst.position,
+            burst.direction,
+            burst.velocity,
+            &mut rng,
+        );
+    }
+}
+
+fn spawn_particles(
+    commands: &mut Commands,
+    particle_meshes: &ParticleMeshes,
+    effect: &ParticleEffect,
+    position: Vec2,
+    direction: Vec2,
+    velocity: Vec2,
+    rng: &mut impl Rng,
+) {
+    let direction = direction.normalize_or(Vec2::Y);
+
+    for _ in 0..rng.gen_range(effect.amount.clone()) {
+        let radius = rng.gen_range(effect.radius.clone());
+        let speed = rng.gen_range(effect.speed.clone());
+        let angle = rng.gen_range(-effect.spread..=effect.spread);
+        let hue_offset = rng.gen_range(-effect.hue_variation..=effect.hue_variation);
+        let (start_color, end_color) = effect.gradient;
+        let particle_velocity = Vec2::from_angle(angle).rotate(direction) * speed
+            + velocity * effect.inherited_velocity;
+
+        commands.spawn_pooled::<Particle>((
+            Transform::from_translation(position.extend(Z_INDEX_PARTICLES))
+                .with_rotation(Quat::from_rotation_z(rng.gen_range(0.0..TAU)))
+                .with_scale(Vec3::new(radius, radius, 1.0)),
+            Visibility::Inherited,
+            Mesh2d(particle_meshes.get(effect.shape)),
+            Particle {
+                lifetime: Timer::from_seconds(effect.lifetime, TimerMode::Once),
+                velocity: particle_velocity,
+                gradient: (
+                    start_color.rotate_hue(hue_offset),
+                    end_color.rotate_hue(hue_offset),
+                ),
+                gravity_ratio: effect.gravity_ratio,
+            },
+        ));
+    }
+}
+
+// Only the new particles need a material, the pooled ones already have one
+fn add_particle_visuals(
+    mut commands: Commands,
+    particle_query: Query<(Entity, &Particle), Added<Particle>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (entity, particle) in particle_query.iter() {
+        commands
+            .entity(entity)
+            .insert(MeshMaterial2d(materials.add(particle.gradient.0)));
+    }
+}
+
+pub fn update_particles(
+    mut commands: Commands,
+    mut particle_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &MeshMaterial2d<ColorMaterial>,
+            &mut Particle,
+        ),
+        Without<Pooled>,
+    >,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    gravity_source: Res<GravitySource>,
+    tuning: Res<Tuning>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+
+    for (entity, mut transform, material_handle, mut particle) in particle_query.iter_mut() {
+        particle.lifetime.tick(time.delta());
+
+        if particle.lifetime.finished() {
+            commands.release::<Particle, ()>(entity);
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+
+        if particle.gravity_ratio != 0.0 && position != Vec2::ZERO {
+            let gravity_force =
+                gravity_force_at(position, gravity_source.force, tuning.gravity_force_scale);
+
+            let acceleration = gravity_force * particle.gravity_ratio / PARTICLE_MASS;
+
+            particle.velocity += acceleration * delta;
+        }
+
+        transform.translation.x += particle.velocity.x * delta;
+        transform.translation.y += particle.velocity.y * delta;
+
+        if let Some(material) = materials.get_mut(&material_handle.0) {
+            let (start_color, end_color) = particle.gradient;
+
+            material.color = start_color.mix(&end_color, particle.lifetime.fraction());
+        }
+    }
+}
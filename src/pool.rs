@@ -0,0 +1,85 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+// Entities of one kind (`K`) that are hidden and waiting to be used again. Reusing them keeps
+// the amount of entities (and their allocations) steady instead of spawning and despawning
+// all the time.
+#[derive(Resource)]
+pub struct Pool<K> {
+    entities: Vec<Entity>,
+    kind: PhantomData<K>,
+}
+
+impl<K> Default for Pool<K> {
+    fn default() -> Self {
+        Pool {
+            entities: Vec::new(),
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<K> Pool<K> {
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+// Marks the entities that are in a pool
+#[derive(Component)]
+pub struct Pooled;
+
+pub trait PoolCommands {
+    // Takes an entity from the pool (or spawns one if the pool is empty) and inserts the bundle
+    fn spawn_pooled<K: Send + Sync + 'static>(&mut self, bundle: impl Bundle);
+
+    // Hides the entity, removes the components `B` (e.g. the physics) and puts it in the pool
+    fn release<K: Send + Sync + 'static, B: Bundle>(&mut self, entity: Entity);
+}
+
+impl PoolCommands for Commands<'_, '_> {
+    fn spawn_pooled<K: Send + Sync + 'static>(&mut self, bundle: impl Bundle) {
+        self.queue(move |world: &mut World| {
+            let pooled_entity = world
+                .get_resource_or_insert_with(Pool::<K>::default)
+                .entities
+                .pop()
+                .and_then(|entity| world.get_entity_mut(entity).ok());
+
+            match pooled_entity {
+                Some(mut entity) => {
+                    entity.remove::<Pooled>().insert(bundle);
+                }
+                None => {
+                    world.spawn(bundle);
+                }
+            }
+        });
+    }
+
+    fn release<K: Send + Sync + 'static, B: Bundle>(&mut self, entity: Entity) {
+        self.queue(move |world: &mut World| {
+            let Ok(mut entity_mut) = world.get_entity_mut(entity) else {
+                return;
+            };
+
+            if entity_mut.contains::<Pooled>() {
+                return;
+            }
+
+            entity_mut
+                .remove::<B>()
+                .insert((Pooled, Visibility::Hidden));
+
+            world
+                .get_resource_or_insert_with(Pool::<K>::default)
+                .entities
+                .push(entity);
+        });
+    }
+}
@@ -0,0 +1,356 @@
+use std::{fs, time::Instant};
+
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, EntityCountDiagnosticsPlugin,
+        FrameTimeDiagnosticsPlugin, RegisterDiagnostic,
+    },
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    app::{AppState, UiConfig, VetovoimaColor},
+    devtools::DevConsole,
+    game::{update_flag_aura, update_gravity_visuals},
+    particles::{emit_particles, update_particles},
+    pool::Pooled,
+    simulation::apply_forces,
+};
+
+const PROFILING_HUD_KEY: KeyCode = KeyCode::F11;
+pub const PROFILE_REPORT_FILENAME: &str = "VVProfile.txt";
+
+// 60 FPS
+pub const FRAME_TIME_BUDGET_MS: f64 = 1000.0 / 60.0;
+const FRAME_TIME_GRAPH_BARS: usize = 120;
+const FRAME_TIME_GRAPH_BAR_WIDTH: f32 = 2.0;
+// The budget is at the middle of the graph, slower frames than twice the budget are cut off
+const FRAME_TIME_GRAPH_HEIGHT: f32 = 60.0;
+const PROFILING_HUD_ALPHA: f32 = 0.85;
+
+pub const APPLY_FORCES_TIME: DiagnosticPath = DiagnosticPath::const_new("apply_forces");
+pub const UPDATE_GRAVITY_VISUALS_TIME: DiagnosticPath =
+    DiagnosticPath::const_new("update_gravity_visuals");
+pub const UPDATE_FLAG_AURA_TIME: DiagnosticPath = DiagnosticPath::const_new("update_flag_aura");
+pub const EMIT_PARTICLES_TIME: DiagnosticPath = DiagnosticPath::const_new("emit_particles");
+pub const UPDATE_PARTICLES_TIME: DiagnosticPath = DiagnosticPath::const_new("update_particles");
+pub const RAPIER_STEP_TIME: DiagnosticPath = DiagnosticPath::const_new("rapier_step");
+pub const RIGID_BODY_COUNT: DiagnosticPath = DiagnosticPath::const_new("rigid_body_count");
+// The hidden debris and particles waiting to be used again
+pub const POOLED_ENTITY_COUNT: DiagnosticPath = DiagnosticPath::const_new("pooled_entity_count");
+
+// In the order they're shown in the HUD and the report
+const PROFILED_DIAGNOSTICS: [DiagnosticPath; 11] = [
+    FrameTimeDiagnosticsPlugin::FRAME_TIME,
+    FrameTimeDiagnosticsPlugin::FPS,
+    RAPIER_STEP_TIME,
+    APPLY_FORCES_TIME,
+    UPDATE_GRAVITY_VISUALS_TIME,
+    UPDATE_FLAG_AURA_TIME,
+    EMIT_PARTICLES_TIME,
+    UPDATE_PARTICLES_TIME,
+    EntityCountDiagnosticsPlugin::ENTITY_COUNT,
+    RIGID_BODY_COUNT,
+    POOLED_ENTITY_COUNT,
+];
+
+// When present, the profiling HUD is shown
+#[derive(Resource)]
+struct ProfilingHud;
+
+// When each timed system (or set) started this frame
+#[derive(Resource, Default)]
+struct SystemTimers(HashMap<DiagnosticPath, Instant>);
+
+#[derive(Component)]
+struct ProfilingHudUi;
+
+#[derive(Component)]
+struct ProfilingText;
+
+#[derive(Component)]
+struct FrameTimeBar(usize);
+
+// Per-system timings, Rapier step time, entity counts and a frame time graph (toggled with
+// PROFILING_HUD_KEY). The "profile" console command writes the same numbers to a file.
+pub struct ProfilingPlugin;
+
+impl Plugin for ProfilingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(EntityCountDiagnosticsPlugin)
+            .register_diagnostic(Diagnostic::new(APPLY_FORCES_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(UPDATE_GRAVITY_VISUALS_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(UPDATE_FLAG_AURA_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(RAPIER_STEP_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(EMIT_PARTICLES_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(UPDATE_PARTICLES_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(RIGID_BODY_COUNT))
+            .register_diagnostic(Diagnostic::new(POOLED_ENTITY_COUNT))
+            .init_resource::<SystemTimers>()
+            .add_systems(
+                Update,
+                (
+                    start_timer(APPLY_FORCES_TIME).before(apply_forces),
+                    stop_timer(APPLY_FORCES_TIME).after(apply_forces),
+                    start_timer(UPDATE_GRAVITY_VISUALS_TIME).before(update_gravity_visuals),
+                    stop_timer(UPDATE_GRAVITY_VISUALS_TIME).after(update_gravity_visuals),
+                    start_timer(UPDATE_FLAG_AURA_TIME).before(update_flag_aura),
+                    stop_timer(UPDATE_FLAG_AURA_TIME).after(update_flag_aura),
+                    start_timer(EMIT_PARTICLES_TIME).before(emit_particles),
+                    stop_timer(EMIT_PARTICLES_TIME).after(emit_particles),
+                    start_timer(UPDATE_PARTICLES_TIME).before(update_particles),
+                    stop_timer(UPDATE_PARTICLES_TIME).after(update_particles),
+                )
+                    .run_if(in_state(AppState::InGame)),
+            )
+            // Rapier runs in PostUpdate by default
+            .add_systems(
+                PostUpdate,
+                (
+                    start_timer(RAPIER_STEP_TIME)
+                        .after(PhysicsSet::SyncBackend)
+                        .before(PhysicsSet::StepSimulation),
+                    stop_timer(RAPIER_STEP_TIME)
+                        .after(PhysicsSet::StepSimulation)
+                        .before(PhysicsSet::Writeback),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    count_entities,
+                    toggle_profiling_hud.run_if(not(resource_exists::<DevConsole>)),
+                    show_profiling_hud.run_if(resource_added::<ProfilingHud>),
+                    profiling_hud_update.run_if(resource_exists::<ProfilingHud>),
+                    hide_profiling_hud.run_if(resource_removed::<ProfilingHud>),
+                )
+                    .chain(),
+            );
+    }
+}
+
+// The timers measure from the outside of the system, so whatever the other threads do in
+// between is included. Good enough for comparing builds, a tracing profiler shows the details.
+fn start_timer(path: DiagnosticPath) -> impl FnMut(ResMut<SystemTimers>) + Send + Sync + 'static {
+    move |mut timers: ResMut<SystemTimers>| {
+        timers.0.insert(path.clone(), Instant::now());
+    }
+}
+
+fn stop_timer(
+    path: DiagnosticPath,
+) -> impl FnMut(ResMut<SystemTimers>, Diagnostics) + Send + Sync + 'static {
+    move |mut timers: ResMut<SystemTimers>, mut diagnostics: Diagnostics| {
+        if let Some(started) = timers.0.remove(&path) {
+            diagnostics.add_measurement(&path, || started.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+}
+
+fn count_entities(
+    mut diagnostics: Diagnostics,
+    rigid_body_query: Query<(), With<RigidBody>>,
+    pooled_query: Query<(), With<Pooled>>,
+) {
+    diagnostics.add_measurement(&RIGID_BODY_COUNT, || rigid_body_query.iter().len() as f64);
+    diagnostics.add_measurement(&POOLED_ENTITY_COUNT, || pooled_query.iter().len() as f64);
+}
+
+fn toggle_profiling_hud(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    profiling_hud: Option<Res<ProfilingHud>>,
+) {
+    if keyboard_input.just_pressed(PROFILING_HUD_KEY) {
+        if profiling_hud.is_some() {
+            commands.remove_resource::<ProfilingHud>();
+        } else {
+            commands.insert_resource(ProfilingHud);
+        }
+    }
+}
+
+fn show_profiling_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    ui_config: Res<UiConfig>,
+) {
+    let font = asset_server.load(ui_config.font_filename);
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(VetovoimaColor::BLACKISH.with_alpha(PROFILING_HUD_ALPHA)),
+            GlobalZIndex(3),
+            ProfilingHudUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::default(),
+                TextFont {
+                    font,
+                    font_size: ui_config.font_size_body_small,
+                    ..Default::default()
+                },
+                TextColor(VetovoimaColor::WHITEISH),
+                ProfilingText,
+            ));
+
+            parent
+                .spawn(Node {
+                    height: Val::Px(FRAME_TIME_GRAPH_HEIGHT),
+                    align_items: AlignItems::FlexEnd,
+                    ..default()
+                })
+                .with_children(|graph| {
+                    for index in 0..FRAME_TIME_GRAPH_BARS {
+                        graph.spawn((
+                            Node {
+                                width: Val::Px(FRAME_TIME_GRAPH_BAR_WIDTH),
+                                height: Val::Px(0.0),
+                                ..default()
+                            },
+                            BackgroundColor(VetovoimaColor::GREENISH),
+                            FrameTimeBar(index),
+                        ));
+                    }
+                });
+        });
+}
+
+fn profiling_hud_update(
+    diagnostics: Res<DiagnosticsStore>,
+    mut text_query: Query<&mut Text, With<ProfilingText>>,
+    mut bar_query: Query<(&mut Node, &mut BackgroundColor, &FrameTimeBar)>,
+) {
+    for mut text in text_query.iter_mut() {
+        **text = profile_lines(&diagnostics).join("\n");
+    }
+
+    // The latest frame is on the right
+    let frame_times: Vec<f64> = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .map(|frame_time| frame_time.values().copied().collect())
+        .unwrap_or_default();
+    let first_bar = FRAME_TIME_GRAPH_BARS.saturating_sub(frame_times.len());
+
+    for (mut node, mut color, bar) in bar_query.iter_mut() {
+        let frame_time = bar
+            .0
+            .checked_sub(first_bar)
+            .and_then(|frame_index| frame_times.get(frame_index));
+
+        let Some(frame_time) = frame_time else {
+            node.height = Val::Px(0.0);
+            continue;
+        };
+
+        let budget_ratio = (*frame_time / FRAME_TIME_BUDGET_MS).min(2.0) as f32;
+
+        node.height = Val::Px(budget_ratio / 2.0 * FRAME_TIME_GRAPH_HEIGHT);
+        color.0 = if *frame_time > FRAME_TIME_BUDGET_MS {
+            VetovoimaColor::REDDISH
+        } else {
+            VetovoimaColor::GREENISH
+        };
+    }
+}
+
+fn hide_profiling_hud(mut commands: Commands, hud_query: Query<Entity, With<ProfilingHudUi>>) {
+    for hud in hud_query.iter() {
+        commands.entity(hud).despawn_recursive();
+    }
+}
+
+// One line per diagnostic (the smoothed value) and how many of the recent frames were too slow
+pub fn profile_lines(diagnostics: &DiagnosticsStore) -> Vec<String> {
+    let mut lines: Vec<String> = PROFILED_DIAGNOSTICS
+        .iter()
+        .filter_map(|path| {
+            let diagnostic = diagnostics.get(path)?;
+            let value = diagnostic.smoothed()?;
+
+            Some(format!("{} {:.2}{}", path, value, diagnostic.suffix))
+        })
+        .collect();
+
+    if let Some((over_budget, frames)) = frames_over_budget(diagnostics) {
+        lines.push(format!("over budget {}/{} frames", over_budget, frames));
+    }
+
+    lines
+}
+
+// The average, min and max of the recent history, for comparing builds
+pub fn profile_report(diagnostics: &DiagnosticsStore) -> String {
+    let build = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+    let mut report = format!(
+        "vetovoima {} ({} build)\nframe time budget {:.2}ms\n\n",
+        env!("CARGO_PKG_VERSION"),
+        build,
+        FRAME_TIME_BUDGET_MS
+    );
+
+    for path in PROFILED_DIAGNOSTICS.iter() {
+        let Some(diagnostic) = diagnostics.get(path) else {
+            continue;
+        };
+        let (Some(average), Some(min), Some(max)) = (
+            diagnostic.average(),
+            diagnostic.values().copied().reduce(f64::min),
+            diagnostic.values().copied().reduce(f64::max),
+        ) else {
+            continue;
+        };
+
+        report.push_str(&format!(
+            "{} average {:.3}{suffix} min {:.3}{suffix} max {:.3}{suffix}\n",
+            path,
+            average,
+            min,
+            max,
+            suffix = diagnostic.suffix
+        ));
+    }
+
+    if let Some((over_budget, frames)) = frames_over_budget(diagnostics) {
+        report.push_str(&format!(
+            "\n{} of the last {} frames were over budget\n",
+            over_budget, frames
+        ));
+    }
+
+    report
+}
+
+// How many of the frames in the history were slower than the budget, and how many there are
+fn frames_over_budget(diagnostics: &DiagnosticsStore) -> Option<(usize, usize)> {
+    let frame_time = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)?;
+    let over_budget = frame_time
+        .values()
+        .filter(|value| **value > FRAME_TIME_BUDGET_MS)
+        .count();
+
+    Some((over_budget, frame_time.history_len()))
+}
+
+pub fn write_profile_report(diagnostics: &DiagnosticsStore, filename: &str) -> Vec<String> {
+    match fs::write(filename, profile_report(diagnostics)) {
+        Ok(()) => vec![format!("Saved to {}", filename)],
+        Err(err) => vec![format!("Could not write {}: {}", filename, err)],
+    }
+}
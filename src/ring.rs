@@ -0,0 +1,102 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    sprite::{AlphaMode2d, Material2d, Material2dPlugin},
+};
+
+const RING_SHADER_PATH: &str = "shaders/ring.wgsl";
+// The quad is a bit larger than the circle so that the line isn't cut off at the edges
+// (same as MESH_HALF_SIZE in the shader)
+const RING_MESH_HALF_SIZE: f32 = 1.1;
+// In world units, like the width of the other strokes
+const RING_LINE_WIDTH: f32 = 1.0;
+
+// An animated circle outline. The radius is the scale of the transform, so growing the ring
+// doesn't require new geometry (and a collider on the same entity is scaled along).
+#[derive(Component)]
+pub struct Ring {
+    pub color: Color,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+pub struct RingMaterial {
+    #[uniform(0)]
+    color: LinearRgba,
+    // Relative to the radius
+    #[uniform(0)]
+    line_width: f32,
+}
+
+impl RingMaterial {
+    fn new(ring: &Ring, transform: &Transform) -> Self {
+        RingMaterial {
+            color: ring.color.into(),
+            line_width: (RING_LINE_WIDTH / transform.scale.x).min(1.0),
+        }
+    }
+}
+
+impl Material2d for RingMaterial {
+    fn fragment_shader() -> ShaderRef {
+        RING_SHADER_PATH.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+// Every ring uses the same quad
+#[derive(Resource)]
+struct RingMesh(Handle<Mesh>);
+
+pub struct RingPlugin;
+
+impl Plugin for RingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(Material2dPlugin::<RingMaterial>::default())
+            .add_systems(Startup, ring_setup)
+            .add_systems(
+                PostUpdate,
+                (add_ring_visuals, update_ring_materials).chain(),
+            );
+    }
+}
+
+pub fn ring_scale(radius: f32) -> Vec3 {
+    Vec3::new(radius, radius, 1.0)
+}
+
+fn ring_setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    let mesh = meshes.add(Rectangle::from_length(2.0 * RING_MESH_HALF_SIZE));
+
+    commands.insert_resource(RingMesh(mesh));
+}
+
+fn add_ring_visuals(
+    mut commands: Commands,
+    ring_query: Query<(Entity, &Ring, &Transform), Added<Ring>>,
+    ring_mesh: Res<RingMesh>,
+    mut materials: ResMut<Assets<RingMaterial>>,
+) {
+    for (entity, ring, transform) in ring_query.iter() {
+        commands.entity(entity).insert((
+            Mesh2d(ring_mesh.0.clone()),
+            MeshMaterial2d(materials.add(RingMaterial::new(ring, transform))),
+        ));
+    }
+}
+
+type ChangedRing = Or<(Changed<Ring>, Changed<Transform>)>;
+
+// Only the uniforms change, the mesh stays the same
+fn update_ring_materials(
+    ring_query: Query<(&Ring, &Transform, &MeshMaterial2d<RingMaterial>), ChangedRing>,
+    mut materials: ResMut<Assets<RingMaterial>>,
+) {
+    for (ring, transform, material_handle) in ring_query.iter() {
+        if let Some(material) = materials.get_mut(&material_handle.0) {
+            *material = RingMaterial::new(ring, transform);
+        }
+    }
+}
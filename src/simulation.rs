@@ -1,14 +1,14 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::ExternalForce;
 
-use crate::app::{ButtonPress, PIXELS_PER_METER};
+use crate::{
+    app::{ButtonPress, PlayerTwoButtonPress, PIXELS_PER_METER},
+    tuning::Tuning,
+};
 
 pub const GRAVITY_SOURCE_RADIUS_METERS: f32 = 2.5;
-// The game world is tiny (less than 30 meters across) and therefore small objects
-// would not have any visible gravity pull without a massive multiplier
-const GRAVITY_FORCE_SCALE: f32 = 250_000.0 * GRAVITY_SOURCE_RADIUS_METERS;
-const MAX_GRAVITY_FORCE: f32 = 1.0;
-const MIN_GRAVITY_FORCE: f32 = -MAX_GRAVITY_FORCE;
+pub const MAX_GRAVITY_FORCE: f32 = 1.0;
+pub const MIN_GRAVITY_FORCE: f32 = -MAX_GRAVITY_FORCE;
 const INITIAL_GRAVITY_FORCE: f32 = MAX_GRAVITY_FORCE;
 const GRAVITY_AUTO_CYCLE_ENABLED_DEFAULT: bool = false;
 
@@ -16,20 +16,23 @@ const GRAVITY_AUTO_CYCLE_ENABLED_DEFAULT: bool = false;
 pub struct GravitySource {
     pub force: f32,
     cycle: Attraction,
-    auto_cycle: bool,
 }
 
+// When present, the gravity cycles between the limits on its own instead of following the input
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct GravityAutoCycle;
+
 enum Attraction {
     Positive,
     Negative,
 }
 
-impl Default for GravitySource {
-    fn default() -> Self {
+impl GravitySource {
+    // The max gravity force can be tuned below the initial force
+    pub fn initial(tuning: &Tuning) -> Self {
         Self {
-            force: INITIAL_GRAVITY_FORCE,
+            force: INITIAL_GRAVITY_FORCE.clamp(-tuning.max_gravity_force, tuning.max_gravity_force),
             cycle: Attraction::Negative,
-            auto_cycle: GRAVITY_AUTO_CYCLE_ENABLED_DEFAULT,
         }
     }
 }
@@ -47,17 +50,22 @@ impl Plugin for SimulationPlugin {
             MAX_GRAVITY_FORCE > MIN_GRAVITY_FORCE,
             "Max gravity should be greater than min gravity",
         );
-        validate_requirement(
-            GRAVITY_FORCE_SCALE > 0.0,
-            "Gravity force scale should be positive",
-        );
         validate_requirement(
             INITIAL_GRAVITY_FORCE <= MAX_GRAVITY_FORCE
                 && INITIAL_GRAVITY_FORCE >= MIN_GRAVITY_FORCE,
             "Initial gravity should be within gravity force bounds",
         );
 
-        app.insert_resource(GravitySource::default());
+        // Unless the tuning plugin is there, the defaults are used
+        app.init_resource::<Tuning>();
+
+        let gravity_source = GravitySource::initial(app.world().resource::<Tuning>());
+
+        app.insert_resource(gravity_source);
+
+        if GRAVITY_AUTO_CYCLE_ENABLED_DEFAULT {
+            app.insert_resource(GravityAutoCycle);
+        }
     }
 }
 
@@ -71,8 +79,12 @@ pub fn update_gravity(
     mut gravity_source: ResMut<GravitySource>,
     timer: Res<Time>,
     button_press: Res<ButtonPress>,
+    player_two_button_press: Res<PlayerTwoButtonPress>,
+    gravity_auto_cycle: Option<Res<GravityAutoCycle>>,
+    tuning: Res<Tuning>,
 ) {
-    let force_change = if gravity_source.auto_cycle {
+    let max_gravity_force = tuning.max_gravity_force;
+    let force_change = if gravity_auto_cycle.is_some() {
         let increment = timer.delta_secs() / 2.0;
 
         match gravity_source.cycle {
@@ -81,10 +93,13 @@ pub fn update_gravity(
         }
     } else {
         let increment = 0.04;
+        // Gravity is shared, so either player may change it
+        let up_pressed = button_press.up_pressed || player_two_button_press.0.up_pressed;
+        let down_pressed = button_press.down_pressed || player_two_button_press.0.down_pressed;
 
-        if button_press.up_pressed {
+        if up_pressed {
             -increment
-        } else if button_press.down_pressed {
+        } else if down_pressed {
             increment
         } else {
             0.0
@@ -93,13 +108,13 @@ pub fn update_gravity(
 
     gravity_source.force += force_change;
 
-    if gravity_source.force >= MAX_GRAVITY_FORCE {
+    if gravity_source.force >= max_gravity_force {
         // Enforce force upper limit
-        gravity_source.force = MAX_GRAVITY_FORCE;
+        gravity_source.force = max_gravity_force;
         gravity_source.cycle = Attraction::Positive;
-    } else if gravity_source.force <= MIN_GRAVITY_FORCE {
+    } else if gravity_source.force <= -max_gravity_force {
         // Enforce force lower limit
-        gravity_source.force = MIN_GRAVITY_FORCE;
+        gravity_source.force = -max_gravity_force;
         gravity_source.cycle = Attraction::Negative;
     }
 }
@@ -107,13 +122,24 @@ pub fn update_gravity(
 pub fn apply_forces(
     mut ext_forces: Query<(&mut ExternalForce, &Transform, &Attractable)>,
     gravity_source: ResMut<GravitySource>,
+    tuning: Res<Tuning>,
 ) {
     for (mut ext_force, transform, attractable) in ext_forces.iter_mut() {
         let translation_2d: Vec2 = Vec2::new(transform.translation.x, transform.translation.y);
+        let gravity_force = gravity_force_at(
+            translation_2d,
+            gravity_source.force,
+            tuning.gravity_force_scale,
+        );
 
-        let force_dir = translation_2d.normalize();
-        let base_force = force_dir * gravity_source.force * GRAVITY_FORCE_SCALE;
-        let gravity_force = base_force / (translation_2d.length() / PIXELS_PER_METER);
         ext_force.force = gravity_force * attractable.force_ratio;
     }
 }
+
+// The pull (or push) of the gravity source weakens with the distance from the center
+pub fn gravity_force_at(position: Vec2, gravity_force: f32, gravity_force_scale: f32) -> Vec2 {
+    let force_dir = position.normalize();
+    let base_force = force_dir * gravity_force * gravity_force_scale;
+
+    base_force / (position.length() / PIXELS_PER_METER)
+}
@@ -2,7 +2,7 @@ use bevy::prelude::*;
 use bevy_kira_audio::{AudioApp, AudioChannel, AudioControl, AudioPlugin, AudioSource};
 use std::marker::PhantomData;
 
-use crate::{game::GameEvent, main_menu::MenuEvent};
+use crate::{attract_mode::AttractMode, config::VVConfig, game::GameEvent, main_menu::MenuEvent};
 
 pub struct SoundsPlugin;
 
@@ -11,6 +11,7 @@ struct ChannelAudioState<T> {
     stopped: bool,
     paused: bool,
     loop_started: bool,
+    // The volume of the latest sound before the volume setting is applied
     volume: f64,
     _marker: PhantomData<T>,
 }
@@ -27,6 +28,13 @@ impl<T> Default for ChannelAudioState<T> {
     }
 }
 
+impl<T: Resource> ChannelAudioState<T> {
+    fn set_volume(&mut self, channel: &AudioChannel<T>, volume: f64, volume_setting: f64) {
+        self.volume = volume;
+        channel.set_volume(volume * volume_setting);
+    }
+}
+
 #[derive(Resource, Component, Default, Clone)]
 struct MainChannel;
 #[derive(Resource, Component, Default, Clone)]
@@ -47,7 +55,15 @@ impl Plugin for SoundsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(AudioPlugin)
             .add_systems(Startup, audio_setup)
-            .add_systems(Update, (process_menu_events, process_game_events))
+            .add_systems(
+                Update,
+                (
+                    process_menu_events,
+                    // The demo is silent
+                    process_game_events.run_if(not(resource_exists::<AttractMode>)),
+                    apply_volume.run_if(resource_changed::<VVConfig>),
+                ),
+            )
             .add_audio_channel::<MainChannel>()
             .add_audio_channel::<EffectChannel>()
             .add_audio_channel::<TransitionChannel>();
@@ -72,17 +88,19 @@ fn process_menu_events(
     mut menu_event: EventReader<MenuEvent>,
     sounds: Res<Sounds>,
     main_channel: Res<AudioChannel<MainChannel>>,
+    mut main_state: ResMut<ChannelAudioState<MainChannel>>,
+    vv_config: Res<VVConfig>,
 ) {
     for event in menu_event.read() {
         match event {
             MenuEvent::EnterMenu => {
                 main_channel.stop();
-                main_channel.set_volume(1.0);
+                main_state.set_volume(&main_channel, 1.0, vv_config.main_volume);
             }
 
-            MenuEvent::BeginNewGame => {
+            MenuEvent::BeginNewGame(_) => {
                 main_channel.stop();
-                main_channel.set_volume(1.0);
+                main_state.set_volume(&main_channel, 1.0, vv_config.main_volume);
                 main_channel.play(sounds.new_game.clone());
             }
         }
@@ -93,7 +111,10 @@ fn process_game_events(
     mut game_event: EventReader<GameEvent>,
     sounds: Res<Sounds>,
     main_channel: Res<AudioChannel<MainChannel>>,
+    mut main_state: ResMut<ChannelAudioState<MainChannel>>,
     effect_channel: Res<AudioChannel<EffectChannel>>,
+    mut effect_state: ResMut<ChannelAudioState<EffectChannel>>,
+    vv_config: Res<VVConfig>,
 ) {
     for event in game_event.read() {
         match event {
@@ -102,7 +123,7 @@ fn process_game_events(
                     match *elapsed_secs {
                         1..=5 => {
                             main_channel.stop();
-                            main_channel.set_volume(0.75);
+                            main_state.set_volume(&main_channel, 0.75, vv_config.main_volume);
                             main_channel.play(sounds.countdown_very_low.clone());
                         }
                         _ => (),
@@ -110,15 +131,15 @@ fn process_game_events(
                 }
             }
 
-            GameEvent::GoalReached => {
+            GameEvent::GoalReached(_) => {
                 main_channel.stop();
-                main_channel.set_volume(1.0);
+                main_state.set_volume(&main_channel, 1.0, vv_config.main_volume);
                 main_channel.play(sounds.reach_goal.clone());
             }
 
             GameEvent::GameOver => {
                 main_channel.stop();
-                main_channel.set_volume(1.0);
+                main_state.set_volume(&main_channel, 1.0, vv_config.main_volume);
                 main_channel.play(sounds.game_over.clone());
             }
 
@@ -132,10 +153,25 @@ fn process_game_events(
                 let volume_coefficient = (total_force_magnitude / 750.0).min(1.0);
                 let volume: f64 = 0.5 * volume_coefficient as f64;
 
-                effect_channel.set_volume(volume);
+                effect_state.set_volume(&effect_channel, volume, vv_config.effects_output_volume());
                 effect_channel.set_playback_rate(playback_rate);
                 effect_channel.play(sounds.bump.clone());
             }
         };
     }
 }
+
+// Whatever is playing follows the volume settings right away, at the volume it was played at
+fn apply_volume(
+    main_channel: Res<AudioChannel<MainChannel>>,
+    main_state: Res<ChannelAudioState<MainChannel>>,
+    effect_channel: Res<AudioChannel<EffectChannel>>,
+    effect_state: Res<ChannelAudioState<EffectChannel>>,
+    transition_channel: Res<AudioChannel<TransitionChannel>>,
+    transition_state: Res<ChannelAudioState<TransitionChannel>>,
+    vv_config: Res<VVConfig>,
+) {
+    main_channel.set_volume(main_state.volume * vv_config.main_volume);
+    effect_channel.set_volume(effect_state.volume * vv_config.effects_output_volume());
+    transition_channel.set_volume(transition_state.volume * vv_config.main_volume);
+}
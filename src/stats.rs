@@ -0,0 +1,196 @@
+use bevy::{prelude::*, utils::SystemTime};
+use serde::Serialize;
+
+use crate::{
+    app::{append_log_line, save_file_path, AppState, ButtonPress, GameMode, PlayerTwoButtonPress},
+    attract_mode::AttractMode,
+    game::{GameEvent, GameLevel},
+    main_menu::MenuEvent,
+};
+
+const STATS_LOG_FILENAME: &str = "VVStats.jsonl";
+const LEVEL_SCORE_BASE: u32 = 100;
+const LEVEL_SCORE_PER_SECOND_LEFT: u32 = 10;
+const LEVEL_SCORE_PER_COLLISION: u32 = 5;
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct LevelStats {
+    pub level: u32,
+    // JSON numbers can't hold every u64 (at least in JavaScript), so the seed is a hex string
+    pub seed: String,
+    // The countdown of the level when it started (the tuning may differ from the defaults)
+    pub countdown_secs: f32,
+    pub secs: f32,
+    pub reached_goal: bool,
+    pub collisions: u32,
+    pub total_collision_force: f32,
+    pub max_collision_force: f32,
+    // How long the gravity controls were held down, and how many times they were pressed
+    pub gravity_input_secs: f32,
+    pub gravity_inputs: u32,
+}
+
+impl LevelStats {
+    // Finishing quickly and without bumping into things gives the best score
+    pub fn score(&self) -> u32 {
+        if !self.reached_goal {
+            return 0;
+        }
+
+        let secs_left = (self.countdown_secs - self.secs).max(0.0) as u32;
+
+        (LEVEL_SCORE_BASE + secs_left * LEVEL_SCORE_PER_SECOND_LEFT)
+            .saturating_sub(self.collisions * LEVEL_SCORE_PER_COLLISION)
+    }
+}
+
+// Statistics of the current (or the latest) run, one entry per played level
+#[derive(Resource, Serialize, Clone, Debug, Default)]
+pub struct RunStats {
+    pub started_at_unix_secs: u64,
+    pub game_mode: String,
+    pub levels: Vec<LevelStats>,
+    #[serde(skip)]
+    logged: bool,
+    #[serde(skip)]
+    gravity_input_held: bool,
+}
+
+impl RunStats {
+    fn new(game_mode: &GameMode) -> Self {
+        let started_at_unix_secs = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        RunStats {
+            started_at_unix_secs,
+            game_mode: format!("{:?}", game_mode),
+            ..default()
+        }
+    }
+
+    // The total on the game over screen, and the score of the daily challenge
+    pub fn score(&self) -> u32 {
+        self.levels.iter().map(LevelStats::score).sum()
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RunStats::default())
+            .add_systems(Update, begin_run)
+            .add_systems(Update, begin_demo_run.run_if(resource_added::<AttractMode>))
+            // The level ends right after the goal is reached or the time runs out
+            .add_systems(
+                PostUpdate,
+                (update_level_stats, process_game_events)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            // A run ends either with the game over or by leaving to the menu
+            .add_systems(OnEnter(AppState::GameOver), log_run)
+            .add_systems(OnEnter(AppState::InMenu), log_run);
+    }
+}
+
+fn begin_run(mut menu_event: EventReader<MenuEvent>, mut run_stats: ResMut<RunStats>) {
+    for event in menu_event.read() {
+        if let MenuEvent::BeginNewGame(game_mode) = event {
+            *run_stats = RunStats::new(game_mode);
+        }
+    }
+}
+
+// The levels of the demo aren't added to the stats of the previous run
+fn begin_demo_run(mut run_stats: ResMut<RunStats>, game_mode: Res<GameMode>) {
+    *run_stats = RunStats::new(&game_mode);
+}
+
+fn update_level_stats(
+    mut run_stats: ResMut<RunStats>,
+    button_press: Res<ButtonPress>,
+    player_two_button_press: Res<PlayerTwoButtonPress>,
+    time: Res<Time>,
+) {
+    let gravity_input_held = button_press.up_pressed
+        || button_press.down_pressed
+        || player_two_button_press.0.up_pressed
+        || player_two_button_press.0.down_pressed;
+    let is_new_gravity_input = gravity_input_held && !run_stats.gravity_input_held;
+
+    run_stats.gravity_input_held = gravity_input_held;
+
+    let Some(level_stats) = run_stats.levels.last_mut() else {
+        return;
+    };
+
+    level_stats.secs += time.delta_secs();
+
+    if gravity_input_held {
+        level_stats.gravity_input_secs += time.delta_secs();
+    }
+
+    if is_new_gravity_input {
+        level_stats.gravity_inputs += 1;
+    }
+}
+
+fn process_game_events(
+    mut game_event: EventReader<GameEvent>,
+    mut run_stats: ResMut<RunStats>,
+    game_level: Option<Res<GameLevel>>,
+) {
+    for event in game_event.read() {
+        match event {
+            GameEvent::LevelStarted => {
+                let Some(game_level) = game_level.as_ref() else {
+                    continue;
+                };
+
+                run_stats.levels.push(LevelStats {
+                    level: game_level.n,
+                    seed: format!("{:016x}", game_level.seed),
+                    countdown_secs: game_level.countdown_to_game_over.duration().as_secs_f32(),
+                    ..default()
+                });
+            }
+
+            GameEvent::GoalReached(_) => {
+                if let Some(level_stats) = run_stats.levels.last_mut() {
+                    level_stats.reached_goal = true;
+                }
+            }
+
+            GameEvent::PlayerCollided(_, total_force_magnitude) => {
+                if let Some(level_stats) = run_stats.levels.last_mut() {
+                    level_stats.collisions += 1;
+                    level_stats.total_collision_force += total_force_magnitude;
+                    level_stats.max_collision_force =
+                        level_stats.max_collision_force.max(*total_force_magnitude);
+                }
+            }
+
+            GameEvent::CountdownTick(_) | GameEvent::GameOver => (),
+        }
+    }
+}
+
+fn log_run(mut run_stats: ResMut<RunStats>, attract_mode: Option<Res<AttractMode>>) {
+    // The demo isn't a real run
+    if run_stats.logged || run_stats.levels.is_empty() || attract_mode.is_some() {
+        return;
+    }
+
+    run_stats.logged = true;
+
+    match serde_json::to_string(&*run_stats) {
+        Ok(line) => append_log_line(&save_file_path(STATS_LOG_FILENAME), &line),
+        Err(err) => {
+            eprintln!("Could not serialize the run statistics");
+            eprintln!("Error: {}", err);
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use bevy::{prelude::*, time::Stopwatch};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::{
+    app::{
+        read_save_file, save_file_path, write_save_file, AppState, ButtonPress, GameMode, UiConfig,
+        VetovoimaColor, TIME_TRIAL_LEVEL_SEEDS,
+    },
+    game::GameEvent,
+    main_menu::MenuEvent,
+};
+
+const TIME_TRIAL_RECORD_FILENAME: &str = "VVTimeTrial.toml";
+
+// Level times of the best completed run
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TimeTrialRecord {
+    pub level_times_secs: Vec<f32>,
+}
+
+impl TimeTrialRecord {
+    fn is_complete(&self) -> bool {
+        self.level_times_secs.len() == TIME_TRIAL_LEVEL_SEEDS.len()
+    }
+
+    fn total_secs(&self) -> Option<f32> {
+        if self.is_complete() {
+            Some(self.level_times_secs.iter().sum())
+        } else {
+            None
+        }
+    }
+
+    // The total time at the end of a level (index starts from zero)
+    fn split_secs(&self, level_index: usize) -> Option<f32> {
+        if self.is_complete() {
+            Some(self.level_times_secs.iter().take(level_index + 1).sum())
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct TimeTrialRun {
+    level_stopwatch: Stopwatch,
+    pub level_times: Vec<Duration>,
+}
+
+impl TimeTrialRun {
+    pub fn total_elapsed(&self) -> Duration {
+        self.level_times.iter().sum::<Duration>() + self.level_stopwatch.elapsed()
+    }
+
+    fn split_secs(&self, level_index: usize) -> f32 {
+        self.level_times
+            .iter()
+            .take(level_index + 1)
+            .sum::<Duration>()
+            .as_secs_f32()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.level_times.len() == TIME_TRIAL_LEVEL_SEEDS.len()
+    }
+}
+
+#[derive(Component)]
+struct TimeTrialUI;
+
+#[derive(Component)]
+struct ElapsedTimeText;
+
+#[derive(Component)]
+struct SplitDeltaText;
+
+#[derive(Component)]
+struct ResultsScreen;
+
+pub struct TimeTrialPlugin;
+
+impl Plugin for TimeTrialPlugin {
+    fn build(&self, app: &mut App) {
+        let record: TimeTrialRecord =
+            read_save_file(&save_file_path(TIME_TRIAL_RECORD_FILENAME)).unwrap_or_default();
+
+        app.insert_resource(record)
+            .insert_resource(TimeTrialRun::default())
+            .add_systems(Update, begin_run)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (reset_level_stopwatch, hud_setup).run_if(resource_equals(GameMode::TimeTrial)),
+            )
+            .add_systems(
+                Update,
+                (update_level_stopwatch, hud_update)
+                    .chain()
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(resource_equals(GameMode::TimeTrial)),
+            )
+            // The goal may be reached at any point during Update, and the state changes right after
+            .add_systems(
+                PostUpdate,
+                record_level_time
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(resource_equals(GameMode::TimeTrial)),
+            )
+            .add_systems(OnExit(AppState::InGame), hud_cleanup)
+            .add_systems(
+                OnEnter(AppState::GameOver),
+                results_screen_setup.run_if(resource_equals(GameMode::TimeTrial)),
+            )
+            .add_systems(
+                Update,
+                results_screen_update
+                    .run_if(in_state(AppState::GameOver))
+                    .run_if(resource_equals(GameMode::TimeTrial)),
+            )
+            .add_systems(OnExit(AppState::GameOver), results_screen_cleanup);
+    }
+}
+
+fn begin_run(mut menu_event: EventReader<MenuEvent>, mut run: ResMut<TimeTrialRun>) {
+    for event in menu_event.read() {
+        if let MenuEvent::BeginNewGame(GameMode::TimeTrial) = event {
+            *run = TimeTrialRun::default();
+        }
+    }
+}
+
+fn reset_level_stopwatch(mut run: ResMut<TimeTrialRun>) {
+    run.level_stopwatch.reset();
+}
+
+fn update_level_stopwatch(mut run: ResMut<TimeTrialRun>, time: Res<Time>) {
+    run.level_stopwatch.tick(time.delta());
+}
+
+fn record_level_time(mut game_event: EventReader<GameEvent>, mut run: ResMut<TimeTrialRun>) {
+    for event in game_event.read() {
+        if let GameEvent::GoalReached(_) = event {
+            let level_time = run.level_stopwatch.elapsed();
+
+            run.level_times.push(level_time);
+            run.level_stopwatch.reset();
+        }
+    }
+}
+
+fn hud_setup(mut commands: Commands, asset_server: Res<AssetServer>, ui_config: Res<UiConfig>) {
+    let font = asset_server.load(ui_config.font_filename);
+
+    commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: font.clone(),
+                font_size: ui_config.font_size_countdown,
+                ..Default::default()
+            },
+            TextColor(VetovoimaColor::WHITEISH),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..default()
+            },
+            TimeTrialUI,
+            ElapsedTimeText,
+        ))
+        .with_child((
+            TextSpan::default(),
+            TextFont {
+                font: font.clone(),
+                font_size: ui_config.font_size_countdown,
+                ..Default::default()
+            },
+            TextColor(VetovoimaColor::WHITEISH),
+            SplitDeltaText,
+        ));
+}
+
+fn hud_update(
+    mut elapsed_text_query: Query<&mut Text, With<ElapsedTimeText>>,
+    mut split_text_query: Query<(&mut TextSpan, &mut TextColor), With<SplitDeltaText>>,
+    run: Res<TimeTrialRun>,
+    record: Res<TimeTrialRecord>,
+) {
+    if let Ok(mut elapsed_text) = elapsed_text_query.get_single_mut() {
+        **elapsed_text = format_time(run.total_elapsed().as_secs_f32());
+    }
+
+    // Compare the latest split against the personal best
+    let Ok((mut split_span, mut split_color)) = split_text_query.get_single_mut() else {
+        return;
+    };
+    let latest_split = run
+        .level_times
+        .len()
+        .checked_sub(1)
+        .and_then(|level_index| {
+            record
+                .split_secs(level_index)
+                .map(|best_split_secs| run.split_secs(level_index) - best_split_secs)
+        });
+
+    match latest_split {
+        Some(delta_secs) => {
+            **split_span = format!(" {}", format_time_delta(delta_secs));
+            **split_color = time_delta_color(delta_secs);
+        }
+        None => {
+            **split_span = String::new();
+        }
+    }
+}
+
+fn hud_cleanup(mut commands: Commands, ui_query: Query<Entity, With<TimeTrialUI>>) {
+    for ui_entity in ui_query.iter() {
+        commands.entity(ui_entity).despawn_recursive();
+    }
+}
+
+fn results_screen_setup(
+    mut commands: Commands,
+    mut record: ResMut<TimeTrialRecord>,
+    run: Res<TimeTrialRun>,
+    asset_server: Res<AssetServer>,
+    ui_config: Res<UiConfig>,
+) {
+    let font = asset_server.load(ui_config.font_filename);
+    let total_secs = run.total_elapsed().as_secs_f32();
+    let previous_best_secs = record.total_secs();
+    let is_personal_best =
+        run.is_complete() && previous_best_secs.is_none_or(|best_secs| total_secs < best_secs);
+
+    let (title, title_color) = if run.is_complete() {
+        ("FINISHED", VetovoimaColor::GREENISH)
+    } else {
+        ("TIME'S UP", VetovoimaColor::REDDISH)
+    };
+
+    let mut rows: Vec<(String, Color)> = Vec::new();
+
+    for (level_index, level_time) in run.level_times.iter().enumerate() {
+        let split_secs = run.split_secs(level_index);
+        let (delta, delta_color) = match record.split_secs(level_index) {
+            Some(best_split_secs) => (
+                format_time_delta(split_secs - best_split_secs),
+                time_delta_color(split_secs - best_split_secs),
+            ),
+            None => (String::new(), VetovoimaColor::WHITEISH),
+        };
+
+        rows.push((
+            format!(
+                "Level {}  {}  {}  {}",
+                level_index + 1,
+                format_time(level_time.as_secs_f32()),
+                format_time(split_secs),
+                delta
+            ),
+            delta_color,
+        ));
+    }
+
+    if !run.is_complete() {
+        rows.push((
+            format!("Level {}  DNF", run.level_times.len() + 1),
+            VetovoimaColor::REDDISH,
+        ));
+    }
+
+    let total_row = if is_personal_best {
+        (
+            format!("Total {}  New personal best!", format_time(total_secs)),
+            VetovoimaColor::YELLOWISH,
+        )
+    } else {
+        match previous_best_secs {
+            Some(best_secs) => (
+                format!(
+                    "Total {}  Personal best {}",
+                    format_time(total_secs),
+                    format_time(best_secs)
+                ),
+                VetovoimaColor::WHITEISH,
+            ),
+            None => (
+                format!("Total {}", format_time(total_secs)),
+                VetovoimaColor::WHITEISH,
+            ),
+        }
+    };
+
+    if is_personal_best {
+        *record = TimeTrialRecord {
+            level_times_secs: run
+                .level_times
+                .iter()
+                .map(|level_time| level_time.as_secs_f32())
+                .collect(),
+        };
+        write_save_file(&save_file_path(TIME_TRIAL_RECORD_FILENAME), &*record);
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(VetovoimaColor::BLACKISH),
+            ResultsScreen,
+        ))
+        .with_children(|container| {
+            container.spawn((
+                Text::new(title),
+                TextFont {
+                    font: font.clone(),
+                    font_size: ui_config.font_size_screen_title,
+                    ..Default::default()
+                },
+                TextColor(title_color),
+            ));
+
+            for (row, color) in rows.into_iter().chain([total_row]) {
+                container.spawn((
+                    Text::new(row),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: ui_config.font_size_countdown,
+                        ..Default::default()
+                    },
+                    TextColor(color),
+                ));
+            }
+        });
+}
+
+fn results_screen_update(
+    button_press: Res<ButtonPress>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if button_press.is_changed() && button_press.main_control_pressed {
+        app_state.set(AppState::InMenu);
+    }
+}
+
+fn results_screen_cleanup(
+    mut commands: Commands,
+    screen_query: Query<Entity, With<ResultsScreen>>,
+) {
+    for screen in screen_query.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+// Helper functions
+
+pub fn format_time(secs: f32) -> String {
+    let minutes = (secs / 60.0).floor();
+    let seconds = secs - (minutes * 60.0);
+
+    format!("{}:{:05.2}", minutes as u32, seconds)
+}
+
+fn format_time_delta(delta_secs: f32) -> String {
+    format!("{:+.2}", delta_secs)
+}
+
+fn time_delta_color(delta_secs: f32) -> Color {
+    if delta_secs <= 0.0 {
+        VetovoimaColor::GREENISH
+    } else {
+        VetovoimaColor::REDDISH
+    }
+}
@@ -0,0 +1,356 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fs};
+
+use crate::simulation::{GRAVITY_SOURCE_RADIUS_METERS, MAX_GRAVITY_FORCE};
+
+// Read from the assets folder, and read again whenever it changes if the game is built with
+// the `hot_reload` feature
+pub const TUNING_ASSET_PATH: &str = "tuning.ron";
+// Same format as the tuning asset, so a snapshot can replace it as is
+pub const TUNING_SNAPSHOT_FILENAME: &str = "VVTuning.ron";
+
+// The names used by the dev console, in the order they're listed. The countdowns and the
+// amounts of objects can only be changed in the tuning asset.
+pub const TUNING_FIELDS: [&str; 15] = [
+    "gravity_force_scale",
+    "max_gravity_force",
+    "player_max_forward_velocity",
+    "player_max_angular_velocity",
+    "player_upright_stiffness",
+    "player_upright_damping",
+    "player_mass",
+    "player_contact_force_threshold",
+    "light_object_density",
+    "medium_object_density",
+    "heavy_object_density",
+    "accel_particle_spawn_interval",
+    "accel_particle_lifetime",
+    "accel_particle_base_speed",
+    "accel_particle_spread",
+];
+
+pub const TUNING_COMMANDS_HELP: [&str; 4] = [
+    "get [name]  show the value(s)",
+    "set <name> <value>  change a value",
+    "reset  go back to the defaults",
+    "save [file]  write the values to VVTuning.ron",
+];
+
+// The countdown is `secs` from this level on (until the next entry)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct LevelCountdown {
+    pub from_level: u32,
+    pub secs: u64,
+}
+
+// The values that decide how the game feels. They can be changed while the game is running
+// (see the dev console and the tuning asset), the defaults are the ones the game ships with.
+#[derive(Asset, TypePath, Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Tuning {
+    // The game world is tiny (less than 30 meters across) and therefore small objects
+    // would not have any visible gravity pull without a massive multiplier
+    pub gravity_force_scale: f32,
+    // The gravity goes from -max to max
+    pub max_gravity_force: f32,
+    pub player_max_forward_velocity: f32,
+    pub player_max_angular_velocity: f32,
+    // The self-righting controller, see `next_upright_angular_velocity`
+    pub player_upright_stiffness: f32,
+    pub player_upright_damping: f32,
+    pub player_mass: f32,
+    // Weaker hits than this don't count as collisions
+    pub player_contact_force_threshold: f32,
+    pub light_object_density: f32,
+    pub medium_object_density: f32,
+    pub heavy_object_density: f32,
+    pub accel_particle_spawn_interval: f32,
+    pub accel_particle_lifetime: f32,
+    pub accel_particle_base_speed: f32,
+    pub accel_particle_spread: f32,
+    // Every level has two objects more than the previous one, up to the max
+    pub base_objects_amount: u32,
+    pub max_objects_amount: u32,
+    pub level_countdowns: Vec<LevelCountdown>,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            gravity_force_scale: 250_000.0 * GRAVITY_SOURCE_RADIUS_METERS,
+            max_gravity_force: MAX_GRAVITY_FORCE,
+            player_max_forward_velocity: 100.0,
+            player_max_angular_velocity: 3.0,
+            // Roughly critically damped (damping = 2 * sqrt(stiffness))
+            player_upright_stiffness: 60.0,
+            player_upright_damping: 15.5,
+            player_mass: 0.15,
+            player_contact_force_threshold: 225.0,
+            light_object_density: 0.75,
+            medium_object_density: 1.0,
+            heavy_object_density: 10.0,
+            accel_particle_spawn_interval: 0.04,
+            accel_particle_lifetime: 0.4,
+            accel_particle_base_speed: 40.0,
+            accel_particle_spread: 35.0,
+            base_objects_amount: 16,
+            max_objects_amount: 60,
+            level_countdowns: vec![
+                LevelCountdown {
+                    from_level: 1,
+                    secs: 60,
+                },
+                LevelCountdown {
+                    from_level: 5,
+                    secs: 40,
+                },
+                LevelCountdown {
+                    from_level: 10,
+                    secs: 30,
+                },
+                LevelCountdown {
+                    from_level: 26,
+                    secs: 20,
+                },
+            ],
+        }
+    }
+}
+
+impl Tuning {
+    pub fn get(&self, field: &str) -> Option<f32> {
+        match field {
+            "gravity_force_scale" => Some(self.gravity_force_scale),
+            "max_gravity_force" => Some(self.max_gravity_force),
+            "player_max_forward_velocity" => Some(self.player_max_forward_velocity),
+            "player_max_angular_velocity" => Some(self.player_max_angular_velocity),
+            "player_upright_stiffness" => Some(self.player_upright_stiffness),
+            "player_upright_damping" => Some(self.player_upright_damping),
+            "player_mass" => Some(self.player_mass),
+            "player_contact_force_threshold" => Some(self.player_contact_force_threshold),
+            "light_object_density" => Some(self.light_object_density),
+            "medium_object_density" => Some(self.medium_object_density),
+            "heavy_object_density" => Some(self.heavy_object_density),
+            "accel_particle_spawn_interval" => Some(self.accel_particle_spawn_interval),
+            "accel_particle_lifetime" => Some(self.accel_particle_lifetime),
+            "accel_particle_base_speed" => Some(self.accel_particle_base_speed),
+            "accel_particle_spread" => Some(self.accel_particle_spread),
+
+            _ => None,
+        }
+    }
+
+    // Every value is a scale, speed, force, stiffness, damping, mass, density or duration, so
+    // they all have to be positive
+    pub fn set(&mut self, field: &str, value: f32) -> Result<(), String> {
+        if !value.is_finite() || value <= 0.0 {
+            return Err(format!("{} must be a positive number", field));
+        }
+
+        let target = match field {
+            "gravity_force_scale" => &mut self.gravity_force_scale,
+            "max_gravity_force" => &mut self.max_gravity_force,
+            "player_max_forward_velocity" => &mut self.player_max_forward_velocity,
+            "player_max_angular_velocity" => &mut self.player_max_angular_velocity,
+            "player_upright_stiffness" => &mut self.player_upright_stiffness,
+            "player_upright_damping" => &mut self.player_upright_damping,
+            "player_mass" => &mut self.player_mass,
+            "player_contact_force_threshold" => &mut self.player_contact_force_threshold,
+            "light_object_density" => &mut self.light_object_density,
+            "medium_object_density" => &mut self.medium_object_density,
+            "heavy_object_density" => &mut self.heavy_object_density,
+            "accel_particle_spawn_interval" => &mut self.accel_particle_spawn_interval,
+            "accel_particle_lifetime" => &mut self.accel_particle_lifetime,
+            "accel_particle_base_speed" => &mut self.accel_particle_base_speed,
+            "accel_particle_spread" => &mut self.accel_particle_spread,
+
+            _ => return Err(format!("Unknown value {}", field)),
+        };
+
+        *target = value;
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = TUNING_FIELDS
+            .iter()
+            .filter(|field| {
+                self.get(field)
+                    .is_some_and(|value| !value.is_finite() || value <= 0.0)
+            })
+            .map(|field| format!("{} must be a positive number", field))
+            .collect();
+
+        if self.max_objects_amount < self.base_objects_amount {
+            errors.push("max_objects_amount can't be less than base_objects_amount".to_string());
+        }
+
+        if self
+            .level_countdowns
+            .first()
+            .map(|countdown| countdown.from_level)
+            != Some(1)
+        {
+            errors.push("level_countdowns must start from level 1".to_string());
+        }
+
+        if self
+            .level_countdowns
+            .windows(2)
+            .any(|pair| pair[0].from_level >= pair[1].from_level)
+        {
+            errors.push("level_countdowns must be in level order".to_string());
+        }
+
+        errors
+    }
+
+    pub fn objects_amount(&self, level_n: u32) -> u32 {
+        (self.base_objects_amount + 2 * level_n).min(self.max_objects_amount)
+    }
+
+    pub fn level_countdown_secs(&self, level_n: u32) -> u64 {
+        self.level_countdowns
+            .iter()
+            .rev()
+            .find(|countdown| countdown.from_level <= level_n)
+            .or(self.level_countdowns.first())
+            .map(|countdown| countdown.secs)
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Default)]
+struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    type Asset = Tuning;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+
+        reader.read_to_end(&mut bytes).await?;
+
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+#[derive(Resource)]
+struct TuningHandle(Handle<Tuning>);
+
+// Without this plugin (e.g. in the headless game) the defaults are used
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tuning>()
+            .init_asset::<Tuning>()
+            .init_asset_loader::<TuningLoader>()
+            .add_systems(Startup, load_tuning)
+            .add_systems(Update, apply_tuning_asset);
+    }
+}
+
+fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_ASSET_PATH)));
+}
+
+// Runs when the asset has loaded, and again every time the file changes
+fn apply_tuning_asset(
+    mut asset_events: EventReader<AssetEvent<Tuning>>,
+    tuning_assets: Res<Assets<Tuning>>,
+    tuning_handle: Res<TuningHandle>,
+    mut tuning: ResMut<Tuning>,
+) {
+    for event in asset_events.read() {
+        if !event.is_loaded_with_dependencies(&tuning_handle.0) {
+            continue;
+        }
+
+        let Some(next_tuning) = tuning_assets.get(&tuning_handle.0) else {
+            continue;
+        };
+        let errors = next_tuning.validate();
+
+        if !errors.is_empty() {
+            eprintln!("Invalid {}, keeping the current values", TUNING_ASSET_PATH);
+
+            for error in errors {
+                eprintln!("  {}", error);
+            }
+
+            continue;
+        }
+
+        if *next_tuning != *tuning {
+            *tuning = next_tuning.clone();
+        }
+    }
+}
+
+// Runs a dev console command and returns the lines to show, or None if the command isn't
+// about the tuning
+pub fn run_tuning_command(tuning: &mut Tuning, command: &str) -> Option<Vec<String>> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let output = match words.as_slice() {
+        ["get"] => TUNING_FIELDS
+            .iter()
+            .filter_map(|field| Some(format!("{} = {}", field, tuning.get(field)?)))
+            .collect(),
+
+        ["get", field] => match tuning.get(field) {
+            Some(value) => vec![format!("{} = {}", field, value)],
+            None => vec![format!("Unknown value {}", field)],
+        },
+
+        ["set", field, value] => {
+            let Ok(value) = value.parse::<f32>() else {
+                return Some(vec![format!("Invalid number {}", value)]);
+            };
+
+            match tuning.set(field, value) {
+                Ok(()) => vec![format!("{} = {}", field, value)],
+                Err(err) => vec![err],
+            }
+        }
+
+        ["reset"] => {
+            *tuning = Tuning::default();
+            vec!["All values reset to the defaults".to_string()]
+        }
+
+        ["save"] => save_snapshot(tuning, TUNING_SNAPSHOT_FILENAME),
+        ["save", filename] => save_snapshot(tuning, filename),
+
+        _ => return None,
+    };
+
+    Some(output)
+}
+
+fn save_snapshot(tuning: &Tuning, filename: &str) -> Vec<String> {
+    let result = ron::ser::to_string_pretty(tuning, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| fs::write(filename, contents).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(()) => vec![format!("Saved to {}", filename)],
+        Err(err) => vec![format!("Could not write {}: {}", filename, err)],
+    }
+}
@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+
+use crate::{
+    app::{
+        AppState, ButtonPress, GameMode, PlayerTwoButtonPress, UiConfig, VetovoimaColor,
+        VERSUS_ROUNDS,
+    },
+    game::{GameEvent, GameLevel, Player},
+    main_menu::MenuEvent,
+};
+
+// Levels (rounds) won by each player
+#[derive(Resource, Default, Debug)]
+pub struct VersusScore {
+    pub player_one: u32,
+    pub player_two: u32,
+}
+
+impl VersusScore {
+    fn winner(&self) -> Option<Player> {
+        if self.player_one > self.player_two {
+            Some(Player::One)
+        } else if self.player_two > self.player_one {
+            Some(Player::Two)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Component)]
+struct VersusUI;
+
+#[derive(Component)]
+struct WinnerScreen;
+
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(VersusScore::default())
+            .add_systems(Update, begin_match)
+            // Not limited to a state, since the level ends right after the goal is reached
+            .add_systems(
+                Update,
+                record_round.run_if(resource_equals(GameMode::Versus)),
+            )
+            .add_systems(
+                OnEnter(AppState::InGame),
+                hud_setup.run_if(resource_equals(GameMode::Versus)),
+            )
+            .add_systems(OnExit(AppState::InGame), hud_cleanup)
+            .add_systems(
+                OnEnter(AppState::GameOver),
+                winner_screen_setup.run_if(resource_equals(GameMode::Versus)),
+            )
+            .add_systems(
+                Update,
+                winner_screen_update
+                    .run_if(in_state(AppState::GameOver))
+                    .run_if(resource_equals(GameMode::Versus)),
+            )
+            .add_systems(OnExit(AppState::GameOver), winner_screen_cleanup);
+    }
+}
+
+fn begin_match(
+    mut menu_event: EventReader<MenuEvent>,
+    mut score: ResMut<VersusScore>,
+    mut player_two_button_press: ResMut<PlayerTwoButtonPress>,
+) {
+    for event in menu_event.read() {
+        if let MenuEvent::BeginNewGame(GameMode::Versus) = event {
+            *score = VersusScore::default();
+            // Keys held down during the previous match shouldn't carry over
+            *player_two_button_press = PlayerTwoButtonPress::default();
+        }
+    }
+}
+
+fn record_round(mut game_event: EventReader<GameEvent>, mut score: ResMut<VersusScore>) {
+    for event in game_event.read() {
+        match event {
+            GameEvent::GoalReached(Player::One) => score.player_one += 1,
+            GameEvent::GoalReached(Player::Two) => score.player_two += 1,
+
+            _ => (),
+        }
+    }
+}
+
+fn hud_setup(
+    mut commands: Commands,
+    score: Res<VersusScore>,
+    game_level: Res<GameLevel>,
+    asset_server: Res<AssetServer>,
+    ui_config: Res<UiConfig>,
+) {
+    let font = asset_server.load(ui_config.font_filename);
+    let text_font = TextFont {
+        font: font.clone(),
+        font_size: ui_config.font_size_countdown,
+        ..Default::default()
+    };
+
+    commands
+        .spawn((
+            Text::new(format!("Round {}/{}  ", game_level.n, VERSUS_ROUNDS)),
+            text_font.clone(),
+            TextColor(VetovoimaColor::WHITEISH),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..default()
+            },
+            VersusUI,
+        ))
+        .with_children(|text| {
+            text.spawn((
+                TextSpan::new(score.player_one.to_string()),
+                text_font.clone(),
+                TextColor(Player::One.color()),
+            ));
+            text.spawn((
+                TextSpan::new(" - "),
+                text_font.clone(),
+                TextColor(VetovoimaColor::WHITEISH),
+            ));
+            text.spawn((
+                TextSpan::new(score.player_two.to_string()),
+                text_font.clone(),
+                TextColor(Player::Two.color()),
+            ));
+        });
+}
+
+fn hud_cleanup(mut commands: Commands, ui_query: Query<Entity, With<VersusUI>>) {
+    for ui_entity in ui_query.iter() {
+        commands.entity(ui_entity).despawn_recursive();
+    }
+}
+
+fn winner_screen_setup(
+    mut commands: Commands,
+    score: Res<VersusScore>,
+    asset_server: Res<AssetServer>,
+    ui_config: Res<UiConfig>,
+) {
+    let font = asset_server.load(ui_config.font_filename);
+    let (title, title_color) = match score.winner() {
+        Some(Player::One) => ("PLAYER 1 WINS", Player::One.color()),
+        Some(Player::Two) => ("PLAYER 2 WINS", Player::Two.color()),
+        None => ("DRAW", VetovoimaColor::WHITEISH),
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(VetovoimaColor::BLACKISH),
+            WinnerScreen,
+        ))
+        .with_children(|container| {
+            container.spawn((
+                Text::new(title),
+                TextFont {
+                    font: font.clone(),
+                    font_size: ui_config.font_size_screen_title,
+                    ..Default::default()
+                },
+                TextColor(title_color),
+            ));
+
+            container.spawn((
+                Text::new(format!("{} - {}", score.player_one, score.player_two)),
+                TextFont {
+                    font: font.clone(),
+                    font_size: ui_config.font_size_countdown,
+                    ..Default::default()
+                },
+                TextColor(VetovoimaColor::WHITEISH),
+            ));
+        });
+}
+
+fn winner_screen_update(
+    button_press: Res<ButtonPress>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if button_press.is_changed() && button_press.main_control_pressed {
+        app_state.set(AppState::InMenu);
+    }
+}
+
+fn winner_screen_cleanup(mut commands: Commands, screen_query: Query<Entity, With<WinnerScreen>>) {
+    for screen in screen_query.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
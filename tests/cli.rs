@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use bevy::window::WindowMode;
+
+use vetovoima::{
+    app::GameMode,
+    cli::{parse_args, CliOptions},
+    config::VVConfig,
+};
+
+fn parse(args: &[&str]) -> Result<CliOptions, String> {
+    parse_args(args.iter().map(|arg| arg.to_string()))
+}
+
+#[test]
+fn no_arguments_shows_the_menu() {
+    let options = parse(&[]).expect("The arguments should be valid");
+
+    assert_eq!(options, CliOptions::default());
+    assert_eq!(options.game_mode(), None);
+}
+
+#[test]
+fn seed_can_be_decimal_or_hex() {
+    assert_eq!(parse(&["--seed", "255"]).unwrap().seed, Some(255));
+    assert_eq!(parse(&["--seed", "0xff"]).unwrap().seed, Some(255));
+    assert_eq!(
+        parse(&["--seed", "0xff"]).unwrap().game_mode(),
+        Some(GameMode::Seeded(255))
+    );
+    assert!(parse(&["--seed", "ff"]).is_err());
+}
+
+#[test]
+fn levels_start_from_one() {
+    assert_eq!(parse(&["--level", "3"]).unwrap().level, Some(3));
+    assert!(parse(&["--level", "0"]).is_err());
+    assert!(parse(&["--level"]).is_err());
+}
+
+#[test]
+fn replay_is_a_time_trial_on_its_own() {
+    let options = parse(&["--replay", "ghost.toml"]).unwrap();
+
+    assert_eq!(options.replay_path, Some(PathBuf::from("ghost.toml")));
+    assert_eq!(options.game_mode(), Some(GameMode::TimeTrial));
+    assert!(parse(&["--replay", "ghost.toml", "--level", "2"]).is_err());
+    assert!(parse(&["--replay", "ghost.toml", "--headless"]).is_err());
+}
+
+#[test]
+fn options_override_the_config() {
+    let options = parse(&["--window-mode", "windowed", "--mute"]).unwrap();
+    let mut config = VVConfig::default();
+
+    options.apply_to_config(&mut config);
+
+    assert_eq!(config.window_mode, WindowMode::Windowed);
+    assert_eq!(config.main_volume, 0.0);
+    assert_eq!(config.effects_volume, 0.0);
+    assert!(parse(&["--window-mode", "maximized"]).is_err());
+}
+
+#[test]
+fn unknown_arguments_are_rejected() {
+    assert!(parse(&["--fast"]).is_err());
+}
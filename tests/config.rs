@@ -0,0 +1,86 @@
+use bevy::window::{MonitorSelection, WindowMode};
+
+use vetovoima::config::{parse_config, ControlLayout, VVConfig, CONFIG_VERSION};
+
+fn error_fields(contents: &str) -> Vec<String> {
+    parse_config(contents)
+        .expect_err("The config should be invalid")
+        .into_iter()
+        .map(|error| error.field)
+        .collect()
+}
+
+#[test]
+fn saved_config_is_read_back() {
+    let config = VVConfig {
+        main_volume: 0.4,
+        colorblind_palette: true,
+        controls: ControlLayout::Wasd,
+        ..VVConfig::default()
+    };
+    let contents = toml::to_string(&config).expect("Could not serialize the config");
+
+    assert_eq!(parse_config(&contents), Ok(config));
+}
+
+#[test]
+fn main_volume_scales_the_effects() {
+    let config = VVConfig {
+        main_volume: 0.5,
+        effects_volume: 0.8,
+        ..VVConfig::default()
+    };
+
+    assert_eq!(config.effects_output_volume(), 0.4);
+    assert_eq!(
+        VVConfig {
+            main_volume: 0.0,
+            ..config
+        }
+        .effects_output_volume(),
+        0.0
+    );
+}
+
+#[test]
+fn missing_values_use_the_defaults() {
+    let config = parse_config(&format!("version = {}\nui_scale = 1.5", CONFIG_VERSION))
+        .expect("The config should be valid");
+
+    assert_eq!(config.ui_scale, 1.5);
+    assert_eq!(config.main_volume, VVConfig::default().main_volume);
+}
+
+#[test]
+fn first_version_is_migrated() {
+    let config = parse_config("window_mode = \"Fullscreen\"\nwindow_width_pixels = 1280")
+        .expect("The config should be migrated");
+
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_eq!(
+        config.window_mode,
+        WindowMode::Fullscreen(MonitorSelection::Primary)
+    );
+    assert_eq!(config.window_width_pixels, Some(1280));
+}
+
+#[test]
+fn every_invalid_value_is_reported() {
+    let contents = format!(
+        "version = {}\nmain_volume = 1.5\ncontrols = \"Mouse\"\nshow_fps = true",
+        CONFIG_VERSION
+    );
+    let mut fields = error_fields(&contents);
+
+    fields.sort();
+
+    assert_eq!(fields, ["controls", "main_volume", "show_fps"]);
+}
+
+#[test]
+fn newer_version_is_rejected() {
+    assert_eq!(
+        error_fields(&format!("version = {}", CONFIG_VERSION + 1)),
+        ["version"]
+    );
+}
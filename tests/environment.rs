@@ -0,0 +1,64 @@
+use vetovoima::environment::{Action, GameEnvironment};
+
+const SEED: u64 = 42;
+
+fn drive_forward() -> Action {
+    Action {
+        forward: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn reset_observes_the_level() {
+    let mut environment = GameEnvironment::new(SEED);
+    let observation = environment.reset(SEED);
+
+    assert!(!observation.debris_positions.is_empty());
+    assert!(observation.secs_remaining > 0.0);
+    assert!(observation.player_position.length() > 0.0);
+}
+
+#[test]
+fn episodes_with_the_same_seed_and_actions_are_identical() {
+    let mut first_environment = GameEnvironment::new(SEED);
+    let mut second_environment = GameEnvironment::new(SEED);
+
+    for _ in 0..30 {
+        let first_step = first_environment.step(drive_forward());
+        let second_step = second_environment.step(drive_forward());
+
+        assert_eq!(first_step, second_step);
+    }
+}
+
+#[test]
+fn episode_is_not_done_right_after_reset() {
+    let mut environment = GameEnvironment::new(SEED);
+
+    let (_, _, done) = environment.step(Action::default());
+
+    assert!(!done);
+}
+
+#[test]
+fn running_out_of_time_ends_the_episode() {
+    let mut environment = GameEnvironment::new(SEED);
+    let mut done = false;
+    let mut last_reward = 0.0;
+
+    // The first level has a 60 second countdown, and a step is a few ticks
+    for _ in 0..2000 {
+        let (_, reward, step_done) = environment.step(Action::default());
+
+        last_reward = reward;
+        done = step_done;
+
+        if done {
+            break;
+        }
+    }
+
+    assert!(done);
+    assert!(last_reward < 0.0);
+}
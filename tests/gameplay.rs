@@ -0,0 +1,400 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::RigidBody;
+use std::time::Duration;
+
+use vetovoima::{
+    app::{AppState, ButtonPress, GameMode},
+    bot::BotDriver,
+    game::{
+        goal_winner, CountdownFrozen, Flag, GameEvent, GameLevel, LevelJump, NextLevelSeed, Player,
+    },
+    headless::HeadlessGame,
+    particles::{Particle, ParticleBurst, ParticleEffect, ParticleShape},
+    pool::Pooled,
+    simulation::{gravity_force_at, MAX_GRAVITY_FORCE, MIN_GRAVITY_FORCE},
+    tuning::Tuning,
+};
+
+fn game_in_first_level(game_mode: GameMode) -> HeadlessGame {
+    let mut game = HeadlessGame::new(game_mode);
+
+    game.start_level();
+    game
+}
+
+fn forward_speed(transform: &Transform, velocity: Vec2) -> f32 {
+    transform.local_x().truncate().dot(velocity)
+}
+
+#[test]
+fn level_starts_after_loading() {
+    // Time trial levels are the same on every run
+    let game = game_in_first_level(GameMode::TimeTrial);
+
+    assert_eq!(game.state(), AppState::InGame);
+    assert_eq!(game.level().map(|level| level.n), Some(1));
+    assert!(game.events().contains(&GameEvent::LevelStarted));
+}
+
+#[test]
+fn gravity_stays_put_without_input() {
+    let mut game = game_in_first_level(GameMode::TimeTrial);
+
+    game.tick(60);
+
+    assert_eq!(game.gravity_force(), MAX_GRAVITY_FORCE);
+}
+
+#[test]
+fn gravity_starts_within_the_tuned_max_force() {
+    let mut game = HeadlessGame::new(GameMode::TimeTrial);
+
+    game.app_mut().insert_resource(Tuning {
+        max_gravity_force: 0.5,
+        ..Tuning::default()
+    });
+    game.start_level();
+
+    assert_eq!(game.gravity_force(), 0.5);
+}
+
+#[test]
+fn holding_up_clamps_gravity_at_min_force() {
+    let mut game = game_in_first_level(GameMode::TimeTrial);
+
+    game.press(ButtonPress {
+        up_pressed: true,
+        ..default()
+    });
+    game.tick(10);
+
+    let force_while_changing = game.gravity_force();
+    assert!(force_while_changing < MAX_GRAVITY_FORCE);
+    assert!(force_while_changing > MIN_GRAVITY_FORCE);
+
+    game.tick(120);
+
+    assert_eq!(game.gravity_force(), MIN_GRAVITY_FORCE);
+}
+
+#[test]
+fn holding_down_clamps_gravity_at_max_force() {
+    let mut game = game_in_first_level(GameMode::TimeTrial);
+
+    game.press(ButtonPress {
+        up_pressed: true,
+        ..default()
+    });
+    game.tick(20);
+    game.press(ButtonPress {
+        down_pressed: true,
+        ..default()
+    });
+    game.tick(120);
+
+    assert_eq!(game.gravity_force(), MAX_GRAVITY_FORCE);
+}
+
+#[test]
+fn gravity_weakens_with_distance() {
+    let tuning = Tuning::default();
+    let near = gravity_force_at(Vec2::new(100.0, 0.0), 1.0, tuning.gravity_force_scale);
+    let far = gravity_force_at(Vec2::new(200.0, 0.0), 1.0, tuning.gravity_force_scale);
+
+    assert!(near.x > 0.0 && near.y == 0.0);
+    assert!((near.x - far.x * 2.0).abs() < near.x * 0.001);
+}
+
+#[test]
+fn holding_right_moves_the_player_forward() {
+    let mut game = game_in_first_level(GameMode::TimeTrial);
+    // Let the player settle on the ground first
+    game.tick(30);
+
+    let (start_transform, _) = game.player(Player::One).expect("The player should exist");
+
+    game.press(ButtonPress {
+        right_pressed: true,
+        ..default()
+    });
+    game.tick(60);
+
+    let (transform, velocity) = game.player(Player::One).expect("The player should exist");
+
+    assert!(forward_speed(&transform, velocity.linvel) > 0.0);
+    assert!(transform.translation.distance(start_transform.translation) > 1.0);
+}
+
+#[test]
+fn countdown_running_out_ends_the_game() {
+    let mut game = game_in_first_level(GameMode::Endless);
+
+    {
+        let mut game_level = game.app_mut().world_mut().resource_mut::<GameLevel>();
+        let countdown_duration = game_level.countdown_to_game_over.duration();
+
+        game_level
+            .countdown_to_game_over
+            .set_elapsed(countdown_duration - Duration::from_millis(100));
+    }
+    game.tick(10);
+
+    assert_eq!(game.state(), AppState::GameOver);
+    assert!(game.events().contains(&GameEvent::GameOver));
+}
+
+#[test]
+fn frozen_countdown_does_not_run_out() {
+    let mut game = game_in_first_level(GameMode::Endless);
+
+    game.app_mut().insert_resource(CountdownFrozen);
+    {
+        let mut game_level = game.app_mut().world_mut().resource_mut::<GameLevel>();
+        let countdown_duration = game_level.countdown_to_game_over.duration();
+
+        game_level
+            .countdown_to_game_over
+            .set_elapsed(countdown_duration - Duration::from_millis(100));
+    }
+    game.tick(10);
+
+    assert_eq!(game.state(), AppState::InGame);
+}
+
+#[test]
+fn level_can_be_regenerated_with_another_seed() {
+    let mut game = game_in_first_level(GameMode::Endless);
+    let seed = game.level().map(|level| level.seed).unwrap_or_default();
+
+    game.app_mut().insert_resource(LevelJump(1));
+    game.app_mut().insert_resource(NextLevelSeed(!seed));
+    game.start_level();
+
+    assert_eq!(game.level().map(|level| level.n), Some(1));
+    assert_eq!(game.level().map(|level| level.seed), Some(!seed));
+}
+
+fn pooled_debris_amount(game: &mut HeadlessGame) -> usize {
+    let world = game.app_mut().world_mut();
+
+    world
+        .query_filtered::<(), (With<Pooled>, Without<Particle>)>()
+        .iter(world)
+        .count()
+}
+
+#[test]
+fn debris_is_kept_for_the_next_level() {
+    // 18 objects on the first level and 20 on the second one
+    let mut game = game_in_first_level(GameMode::TimeTrial);
+
+    game.set_state(AppState::InMenu);
+
+    assert_eq!(pooled_debris_amount(&mut game), 18);
+
+    game.start_level();
+
+    assert_eq!(game.level().map(|level| level.n), Some(2));
+    assert_eq!(pooled_debris_amount(&mut game), 0);
+}
+
+fn particles_amount(game: &mut HeadlessGame) -> usize {
+    let world = game.app_mut().world_mut();
+
+    world
+        .query_filtered::<(), (With<Particle>, Without<Pooled>)>()
+        .iter(world)
+        .count()
+}
+
+fn pooled_particles_amount(game: &mut HeadlessGame) -> usize {
+    let world = game.app_mut().world_mut();
+
+    world
+        .query_filtered::<(), (With<Particle>, With<Pooled>)>()
+        .iter(world)
+        .count()
+}
+
+// Without the debris there are no impacts (or particles) other than the ones of the test
+fn remove_debris(game: &mut HeadlessGame) {
+    let world = game.app_mut().world_mut();
+    let debris: Vec<Entity> = world
+        .query_filtered::<(Entity, &RigidBody), Without<Player>>()
+        .iter(world)
+        .filter(|(_, rigid_body)| **rigid_body == RigidBody::Dynamic)
+        .map(|(entity, _)| entity)
+        .collect();
+
+    for entity in debris {
+        world.despawn(entity);
+    }
+}
+
+#[test]
+fn exhaust_particles_go_to_the_pool_when_they_fade() {
+    let mut game = game_in_first_level(GameMode::TimeTrial);
+
+    remove_debris(&mut game);
+    game.press(ButtonPress {
+        right_pressed: true,
+        ..default()
+    });
+    game.tick(10);
+
+    assert!(particles_amount(&mut game) > 0);
+
+    game.press(ButtonPress::default());
+    game.tick(60);
+
+    assert_eq!(particles_amount(&mut game), 0);
+    assert!(pooled_particles_amount(&mut game) > 0);
+}
+
+#[test]
+fn particles_follow_the_gravity() {
+    let mut game = game_in_first_level(GameMode::TimeTrial);
+
+    let start_position = Vec2::new(0.0, 300.0);
+
+    remove_debris(&mut game);
+    // The particles of the earlier impacts fade first
+    game.tick(90);
+
+    assert_eq!(particles_amount(&mut game), 0);
+
+    game.app_mut().world_mut().send_event(ParticleBurst {
+        effect: ParticleEffect {
+            shape: ParticleShape::Circle,
+            gradient: (Color::WHITE, Color::WHITE),
+            hue_variation: 0.0,
+            lifetime: 1.0,
+            amount: 1..=1,
+            radius: 1.0..=1.0,
+            speed: 0.0..=0.0,
+            spread: 0.0,
+            inherited_velocity: 0.0,
+            gravity_ratio: 1.0,
+        },
+        position: start_position,
+        direction: Vec2::Y,
+        velocity: Vec2::ZERO,
+    });
+    game.tick(30);
+
+    let world = game.app_mut().world_mut();
+    let particle_position = world
+        .query_filtered::<&Transform, (With<Particle>, Without<Pooled>)>()
+        .single(world)
+        .translation
+        .truncate();
+
+    // The gravity is at max force, which pushes everything towards the terrain
+    assert!(particle_position.length() > start_position.length());
+}
+
+#[test]
+fn reaching_the_flag_loads_the_next_level() {
+    let mut game = game_in_first_level(GameMode::TimeTrial);
+    game.clear_events();
+
+    let world = game.app_mut().world_mut();
+    let flag_transform = *world
+        .query_filtered::<&Transform, With<Flag>>()
+        .single(world);
+    let mut player_transform = world
+        .query_filtered::<&mut Transform, (With<Player>, Without<Flag>)>()
+        .single_mut(world);
+    player_transform.translation = flag_transform.translation;
+    player_transform.rotation = flag_transform.rotation;
+
+    game.tick(2);
+
+    assert!(game.events().contains(&GameEvent::GoalReached(Player::One)));
+    assert_eq!(game.state(), AppState::LoadingLevel);
+
+    game.start_level();
+
+    assert_eq!(game.level().map(|level| level.n), Some(2));
+}
+
+#[test]
+fn closer_player_takes_the_flag_when_both_reach_it() {
+    let mut game = game_in_first_level(GameMode::Versus);
+    game.clear_events();
+
+    let world = game.app_mut().world_mut();
+    let flag_transform = *world
+        .query_filtered::<&Transform, With<Flag>>()
+        .single(world);
+    let flag_up = flag_transform.local_y().truncate();
+
+    for (mut player_transform, player) in world
+        .query_filtered::<(&mut Transform, &Player), Without<Flag>>()
+        .iter_mut(world)
+    {
+        // Both touch the flag, player two right at its center
+        let offset = match player {
+            Player::One => flag_up * 4.0,
+            Player::Two => Vec2::ZERO,
+        };
+
+        player_transform.translation = flag_transform.translation + offset.extend(0.0);
+        player_transform.rotation = flag_transform.rotation;
+    }
+
+    game.tick(2);
+
+    assert!(game.events().contains(&GameEvent::GoalReached(Player::Two)));
+    assert!(!game.events().contains(&GameEvent::GoalReached(Player::One)));
+}
+
+#[test]
+fn goal_winner_is_decided_by_the_distance_to_the_flag() {
+    assert_eq!(goal_winner([]), None);
+    assert_eq!(
+        goal_winner([(Player::One, 12.0), (Player::Two, 3.0)]),
+        Some(Player::Two)
+    );
+    // A tie goes to player one, whatever the order
+    assert_eq!(
+        goal_winner([(Player::Two, 5.0), (Player::One, 5.0)]),
+        Some(Player::One)
+    );
+}
+
+#[test]
+fn versus_players_have_their_own_controls() {
+    let mut game = game_in_first_level(GameMode::Versus);
+    game.tick(30);
+
+    game.press_player_two(ButtonPress {
+        right_pressed: true,
+        ..default()
+    });
+    game.tick(60);
+
+    let (player_one_transform, player_one_velocity) =
+        game.player(Player::One).expect("Player one should exist");
+    let (player_two_transform, player_two_velocity) =
+        game.player(Player::Two).expect("Player two should exist");
+
+    assert!(
+        forward_speed(&player_two_transform, player_two_velocity.linvel)
+            > forward_speed(&player_one_transform, player_one_velocity.linvel)
+    );
+}
+
+#[test]
+fn bot_drives_the_player_forward() {
+    let mut game = HeadlessGame::new(GameMode::TimeTrial);
+    game.app_mut().insert_resource(BotDriver::default());
+    game.start_level();
+
+    game.tick(90);
+
+    let (transform, velocity) = game.player(Player::One).expect("The player should exist");
+
+    assert!(game.app().world().resource::<ButtonPress>().right_pressed);
+    assert!(forward_speed(&transform, velocity.linvel) > 0.0);
+}
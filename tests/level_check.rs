@@ -0,0 +1,46 @@
+use vetovoima::{
+    level_check::{check_level, check_level_with_tuning, LevelCheckOutcome},
+    tuning::{LevelCountdown, Tuning},
+};
+
+fn tuning_with_countdown(secs: u64) -> Tuning {
+    Tuning {
+        level_countdowns: vec![LevelCountdown {
+            from_level: 1,
+            secs,
+        }],
+        ..Tuning::default()
+    }
+}
+
+#[test]
+fn level_check_is_repeatable() {
+    let first_check = check_level(7, 1);
+    let second_check = check_level(7, 1);
+
+    assert_eq!(first_check.outcome, second_check.outcome);
+}
+
+#[test]
+fn solved_levels_are_solved_within_the_countdown() {
+    let countdown_secs = 300;
+    // With plenty of time the bot gets around the level, on one seed if not another
+    let solve_time = (1..=4)
+        .map(|seed| check_level_with_tuning(seed, 1, tuning_with_countdown(countdown_secs)))
+        .find_map(|level_check| match level_check.outcome {
+            LevelCheckOutcome::Solved(secs) => Some(secs),
+            LevelCheckOutcome::Unsolved => None,
+        })
+        .expect("Expected the bot to solve a level with plenty of time");
+
+    assert!(solve_time > 0.0 && solve_time <= countdown_secs as f32);
+}
+
+#[test]
+fn unsolved_levels_are_reported() {
+    // Nobody can drive to the flag in a second
+    let level_check = check_level_with_tuning(7, 1, tuning_with_countdown(1));
+
+    assert_eq!(level_check.outcome, LevelCheckOutcome::Unsolved);
+    assert!(level_check.to_string().ends_with("NOT SOLVED"));
+}
@@ -0,0 +1,52 @@
+use std::time::Instant;
+
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticMeasurement, DiagnosticsStore, FrameTimeDiagnosticsPlugin,
+};
+use vetovoima::profiling::{profile_report, APPLY_FORCES_TIME};
+
+fn diagnostics_with(frame_times: &[f64], apply_forces_times: &[f64]) -> DiagnosticsStore {
+    let mut diagnostics = DiagnosticsStore::default();
+    let mut frame_time = Diagnostic::new(FrameTimeDiagnosticsPlugin::FRAME_TIME).with_suffix("ms");
+    let mut apply_forces = Diagnostic::new(APPLY_FORCES_TIME).with_suffix("ms");
+
+    for value in frame_times {
+        frame_time.add_measurement(DiagnosticMeasurement {
+            time: Instant::now(),
+            value: *value,
+        });
+    }
+
+    for value in apply_forces_times {
+        apply_forces.add_measurement(DiagnosticMeasurement {
+            time: Instant::now(),
+            value: *value,
+        });
+    }
+
+    diagnostics.add(frame_time);
+    diagnostics.add(apply_forces);
+    diagnostics
+}
+
+#[test]
+fn report_has_the_timings() {
+    let report = profile_report(&diagnostics_with(&[10.0, 20.0], &[0.5, 1.5]));
+
+    assert!(report.contains("frame_time average 15.000ms min 10.000ms max 20.000ms"));
+    assert!(report.contains("apply_forces average 1.000ms min 0.500ms max 1.500ms"));
+}
+
+#[test]
+fn report_counts_the_frames_over_budget() {
+    let report = profile_report(&diagnostics_with(&[10.0, 20.0, 30.0, 16.0], &[]));
+
+    assert!(report.contains("2 of the last 4 frames were over budget"));
+}
+
+#[test]
+fn diagnostics_without_measurements_are_left_out() {
+    let report = profile_report(&diagnostics_with(&[10.0], &[]));
+
+    assert!(!report.contains("apply_forces"));
+}
@@ -0,0 +1,130 @@
+use bevy::{input::InputPlugin, window::CursorMoved};
+
+use vetovoima::{
+    app::{AppState, GameMode},
+    attract_mode::{AttractMode, AttractModePlugin},
+    headless::HeadlessGame,
+    main_menu::MenuEvent,
+    stats::{LevelStats, RunStats, StatsPlugin},
+    tuning::{LevelCountdown, Tuning},
+};
+
+fn completed_level(secs: f32, collisions: u32) -> LevelStats {
+    LevelStats {
+        level: 1,
+        countdown_secs: 60.0,
+        secs,
+        reached_goal: true,
+        collisions,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn unfinished_level_scores_nothing() {
+    let level_stats = LevelStats {
+        reached_goal: false,
+        ..completed_level(10.0, 0)
+    };
+
+    assert_eq!(level_stats.score(), 0);
+}
+
+#[test]
+fn faster_level_scores_more() {
+    assert!(completed_level(10.0, 0).score() > completed_level(20.0, 0).score());
+}
+
+#[test]
+fn collisions_reduce_the_score() {
+    assert!(completed_level(10.0, 0).score() > completed_level(10.0, 3).score());
+    // The score doesn't wrap around, no matter how bumpy the ride was
+    assert_eq!(completed_level(60.0, 1000).score(), 0);
+}
+
+#[test]
+fn run_scores_the_finished_levels() {
+    let mut run_stats = RunStats::default();
+
+    run_stats.levels = vec![
+        completed_level(10.0, 0),
+        completed_level(20.0, 2),
+        LevelStats {
+            reached_goal: false,
+            ..completed_level(60.0, 0)
+        },
+    ];
+
+    assert_eq!(
+        run_stats.score(),
+        completed_level(10.0, 0).score() + completed_level(20.0, 2).score()
+    );
+}
+
+#[test]
+fn level_is_scored_against_its_own_countdown() {
+    let mut game = HeadlessGame::new(GameMode::TimeTrial);
+
+    game.app_mut()
+        .add_plugins(StatsPlugin)
+        .add_event::<MenuEvent>();
+    game.app_mut()
+        .world_mut()
+        .resource_mut::<Tuning>()
+        .level_countdowns = vec![LevelCountdown {
+        from_level: 1,
+        secs: 30,
+    }];
+    game.start_level();
+    game.tick(1);
+
+    let run_stats = game.app().world().resource::<RunStats>();
+    let level_stats = run_stats
+        .levels
+        .last()
+        .expect("The level should have stats");
+
+    assert_eq!(level_stats.countdown_secs, 30.0);
+    // Out of time with the tuned countdown, even though the default one would have time left
+    assert_eq!(
+        LevelStats {
+            secs: 40.0,
+            reached_goal: true,
+            collisions: 0,
+            ..level_stats.clone()
+        }
+        .score(),
+        completed_level(60.0, 0).score()
+    );
+}
+
+#[test]
+fn demo_starts_a_run_of_its_own() {
+    let mut game = HeadlessGame::new(GameMode::TimeTrial);
+
+    game.app_mut()
+        .add_plugins((InputPlugin, StatsPlugin, AttractModePlugin))
+        .add_event::<MenuEvent>()
+        .add_event::<CursorMoved>();
+    game.set_state(AppState::InMenu);
+    // The stats of the previous run are kept while in the menu
+    {
+        let mut run_stats = game.app_mut().world_mut().resource_mut::<RunStats>();
+
+        run_stats.game_mode = format!("{:?}", GameMode::TimeTrial);
+        run_stats.levels.push(completed_level(10.0, 0));
+    }
+
+    // The demo begins once the menu has been idle for a while
+    game.tick(11 * 60);
+
+    assert!(game.app().world().contains_resource::<AttractMode>());
+
+    let run_stats = game.app().world().resource::<RunStats>();
+
+    assert_eq!(run_stats.game_mode, format!("{:?}", GameMode::Endless));
+    assert!(run_stats
+        .levels
+        .iter()
+        .all(|level_stats| level_stats.secs < 10.0));
+}
@@ -0,0 +1,95 @@
+use vetovoima::tuning::{run_tuning_command, LevelCountdown, Tuning, TUNING_FIELDS};
+
+fn run_console_command(tuning: &mut Tuning, command: &str) -> Vec<String> {
+    run_tuning_command(tuning, command).expect("The command should be a tuning command")
+}
+
+#[test]
+fn every_listed_value_can_be_read() {
+    let tuning = Tuning::default();
+
+    for field in TUNING_FIELDS {
+        assert!(tuning.get(field).is_some(), "{} can't be read", field);
+    }
+}
+
+#[test]
+fn set_command_changes_the_value() {
+    let mut tuning = Tuning::default();
+    let output = run_console_command(&mut tuning, "set player_max_forward_velocity 120");
+
+    assert_eq!(
+        output,
+        vec!["player_max_forward_velocity = 120".to_string()]
+    );
+    assert_eq!(tuning.player_max_forward_velocity, 120.0);
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    let mut tuning = Tuning::default();
+
+    run_console_command(&mut tuning, "set heavy_object_density -1");
+    run_console_command(&mut tuning, "set heavy_object_density heavy");
+    run_console_command(&mut tuning, "set gravity 2");
+
+    assert_eq!(tuning, Tuning::default());
+    assert_eq!(run_tuning_command(&mut tuning, "level 2"), None);
+}
+
+#[test]
+fn get_command_lists_every_value() {
+    let mut tuning = Tuning::default();
+
+    assert_eq!(
+        run_console_command(&mut tuning, "get").len(),
+        TUNING_FIELDS.len()
+    );
+    assert_eq!(
+        run_console_command(&mut tuning, "get player_max_angular_velocity"),
+        vec!["player_max_angular_velocity = 3".to_string()]
+    );
+}
+
+#[test]
+fn tuning_asset_has_the_defaults() {
+    let contents =
+        std::fs::read_to_string("assets/tuning.ron").expect("The tuning asset is missing");
+    let tuning: Tuning = ron::from_str(&contents).expect("The tuning asset should be valid");
+
+    assert_eq!(tuning, Tuning::default());
+    assert!(tuning.validate().is_empty());
+}
+
+#[test]
+fn countdown_follows_the_table() {
+    let tuning = Tuning::default();
+
+    assert_eq!(tuning.level_countdown_secs(1), 60);
+    assert_eq!(tuning.level_countdown_secs(4), 60);
+    assert_eq!(tuning.level_countdown_secs(5), 40);
+    assert_eq!(tuning.level_countdown_secs(25), 30);
+    assert_eq!(tuning.level_countdown_secs(100), 20);
+}
+
+#[test]
+fn countdowns_must_start_from_the_first_level() {
+    let tuning = Tuning {
+        level_countdowns: vec![LevelCountdown {
+            from_level: 2,
+            secs: 60,
+        }],
+        ..Tuning::default()
+    };
+
+    assert_eq!(tuning.validate().len(), 1);
+}
+
+#[test]
+fn objects_amount_grows_up_to_the_max() {
+    let tuning = Tuning::default();
+
+    assert_eq!(tuning.objects_amount(1), 18);
+    assert_eq!(tuning.objects_amount(10), 36);
+    assert_eq!(tuning.objects_amount(30), 60);
+}
@@ -0,0 +1,62 @@
+use bevy::math::Vec2;
+use std::f32::consts::PI;
+
+use vetovoima::{
+    game::{next_upright_angular_velocity, signed_angle_between},
+    tuning::Tuning,
+};
+
+const DELTA_SECS: f32 = 1.0 / 60.0;
+
+fn correction(angle_error: f32) -> f32 {
+    next_upright_angular_velocity(&Tuning::default(), angle_error, 0.0, DELTA_SECS)
+}
+
+#[test]
+fn correction_shrinks_as_the_error_nears_zero() {
+    let large = correction(0.5).abs();
+    let small = correction(0.1).abs();
+
+    assert!(large > small);
+    assert!(small > 0.0);
+    assert_eq!(correction(0.0), 0.0);
+}
+
+#[test]
+fn correction_turns_towards_upright() {
+    // Upright is +Y, tilted clockwise the player has to turn counterclockwise and vice versa
+    let tilted_clockwise = signed_angle_between(Vec2::new(1.0, 1.0), Vec2::Y);
+    let tilted_counterclockwise = signed_angle_between(Vec2::new(-1.0, 1.0), Vec2::Y);
+
+    assert!(tilted_clockwise > 0.0);
+    assert!(tilted_counterclockwise < 0.0);
+    assert!(correction(tilted_clockwise) > 0.0);
+    assert!(correction(tilted_counterclockwise) < 0.0);
+}
+
+#[test]
+fn upside_down_player_still_gets_a_correction() {
+    let angle_error = signed_angle_between(Vec2::NEG_Y, Vec2::Y);
+
+    assert!((angle_error.abs() - PI).abs() < 1e-5);
+
+    let next_angular_velocity = correction(angle_error);
+
+    assert!(next_angular_velocity.is_finite());
+    assert!(next_angular_velocity != 0.0);
+}
+
+#[test]
+fn correction_is_clamped_to_the_max_angular_velocity() {
+    let tuning = Tuning {
+        player_upright_stiffness: 10_000.0,
+        player_max_angular_velocity: 3.0,
+        ..Tuning::default()
+    };
+
+    let counterclockwise = next_upright_angular_velocity(&tuning, 1.0, 0.0, DELTA_SECS);
+    let clockwise = next_upright_angular_velocity(&tuning, -1.0, 0.0, DELTA_SECS);
+
+    assert_eq!(counterclockwise, 3.0);
+    assert_eq!(clockwise, -3.0);
+}